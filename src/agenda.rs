@@ -0,0 +1,120 @@
+use chrono::{DateTime, Days, Duration, Local, NaiveDate};
+
+use crate::config::Config;
+use crate::scheduler::{resolve_local, ForecastStep, Task, Tasks};
+
+/// A plan for the rest of the day: what's late, what's due, and what
+/// the scheduler expects to work on
+#[derive(Clone, PartialEq, Debug)]
+pub struct Agenda {
+    pub date: NaiveDate,
+    /// Tasks already past their deadline, other than chores
+    pub overdue: Vec<Task>,
+    /// Tasks due later today, other than chores
+    pub due_today: Vec<Task>,
+    /// Recurring tasks due by the end of today, late or not
+    pub chores: Vec<Task>,
+    /// Steps the scheduler expects to get through before the day ends
+    pub planned: Vec<ForecastStep>,
+}
+
+/// One line of the agenda
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub task_id: u64,
+    pub name: String,
+    pub detail: String,
+}
+
+impl Agenda {
+    /// Plan the rest of the day from `now`, forecasting up to `n_steps`
+    /// steps of `step_length` each
+    pub fn plan(
+        tasks: &Tasks,
+        now: DateTime<Local>,
+        n_steps: usize,
+        step_length: Duration,
+    ) -> Self {
+        let date = now.date_naive();
+        let end_of_day = date
+            .checked_add_days(Days::new(1))
+            .and_then(|x| resolve_local(&Local, x.and_hms_opt(0, 0, 0).unwrap()))
+            .unwrap_or(now);
+        let mut due: Vec<&Task> = tasks
+            .tasks
+            .iter()
+            .filter(|x| x.deadline.is_some_and(|x| x < end_of_day))
+            .collect();
+        due.sort_by_key(|x| x.deadline);
+        let (chores, other): (Vec<&Task>, Vec<&Task>) =
+            due.into_iter().partition(|x| x.recurrence.is_some());
+        let (overdue, due_today): (Vec<&Task>, Vec<&Task>) =
+            other.into_iter().partition(|x| x.is_overdue(now));
+        let mut planned = tasks.simulate_from(n_steps, now, step_length).steps;
+        planned.retain(|x| x.time < end_of_day);
+        Agenda {
+            date,
+            overdue: overdue.into_iter().cloned().collect(),
+            due_today: due_today.into_iter().cloned().collect(),
+            chores: chores.into_iter().cloned().collect(),
+            planned,
+        }
+    }
+
+    /// The sections with anything in them, with a title for each
+    pub fn sections(&self, config: &Config) -> Vec<(&'static str, Vec<Entry>)> {
+        let due = |tasks: &[Task]| -> Vec<Entry> {
+            tasks
+                .iter()
+                .map(|x| Entry {
+                    task_id: x.id,
+                    name: x.name.clone(),
+                    detail: format!("due {}", config.format_deadline(&x.deadline.unwrap())),
+                })
+                .collect()
+        };
+        let planned = self
+            .planned
+            .iter()
+            .map(|x| Entry {
+                task_id: x.task_id,
+                name: x.name.clone(),
+                detail: format!("at {}", x.time.format(&config.time_format)),
+            })
+            .collect();
+        let sections = vec![
+            ("Overdue", due(&self.overdue)),
+            ("Due Today", due(&self.due_today)),
+            ("Chores", due(&self.chores)),
+            ("Plan", planned),
+        ];
+        sections.into_iter().filter(|x| !x.1.is_empty()).collect()
+    }
+
+    /// The agenda as plain text, or as Markdown with a checklist for
+    /// each section
+    pub fn to_text(&self, config: &Config, markdown: bool) -> String {
+        let title = format!("Today, {}", self.date.format(&config.date_format));
+        let mut out = if markdown {
+            format!("# {}\n", title)
+        } else {
+            format!("{}\n", title)
+        };
+        let sections = self.sections(config);
+        if sections.is_empty() {
+            out += "\nNothing to do today\n";
+        }
+        for (heading, entries) in sections {
+            if markdown {
+                out += &format!("\n## {}\n\n", heading);
+            } else {
+                out += &format!("\n{}\n", heading);
+            }
+            for entry in entries {
+                let bullet = if markdown { "- [ ]" } else { " " };
+                out += &format!("{} {} ({})\n", bullet, entry.name, entry.detail);
+            }
+        }
+        out
+    }
+}
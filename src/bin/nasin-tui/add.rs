@@ -1,11 +1,8 @@
-use chrono::{Local, NaiveDate, NaiveDateTime};
-use nasin::scheduler::Task;
+use nasin::scheduler::{parse_deadline, Task};
 use ratatui::{
     layout::{Constraint, Layout},
     prelude::StatefulWidget,
     prelude::{Buffer, Rect},
-    style::Stylize,
-    widgets::{Paragraph, Widget},
 };
 
 use tui_widgets::prompts::{State, TextPrompt, TextState};
@@ -50,7 +47,7 @@ impl Popup<'_> {
             .split(area);
         TextPrompt::new("Name".into()).render(layout[0], buf, &mut self.name);
         TextPrompt::new("Priority".into()).render(layout[1], buf, &mut self.priority);
-        TextPrompt::new("Date".into()).render(layout[2], buf, &mut self.date);
+        TextPrompt::new("Date (YYYY-MM-DD [HH:MM])".into()).render(layout[2], buf, &mut self.date);
     }
 
     pub fn focus_down(&mut self) {
@@ -77,15 +74,17 @@ impl Popup<'_> {
         }
     }
 
-    pub fn to_task(&self) -> Option<Task> {
-        let priority: u8 = self.priority.value().parse().unwrap_or(1);
+    pub fn to_task(&self, default_priority: u8) -> Option<Task> {
+        let priority: u8 = self.priority.value().parse().unwrap_or(default_priority);
+        // 0 is kept for overdue tasks
+        if priority == 0 {
+            return None;
+        }
         let deadline_str = self.date.value();
         let deadline = if deadline_str.is_empty() {
             None
         } else {
-            let date = NaiveDate::parse_from_str(deadline_str, "%Y-%m-%d").ok()?;
-            let datetime: NaiveDateTime = date.into();
-            Some(datetime.and_local_timezone(Local).unwrap())
+            Some(parse_deadline(deadline_str)?)
         };
         Some(Task::new(
             String::from(self.name.value()),
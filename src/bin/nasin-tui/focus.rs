@@ -0,0 +1,68 @@
+use chrono::Duration;
+use nasin::config::Config;
+use nasin::scheduler::{format_estimate, format_timer, Task};
+use ratatui::{
+    layout::{Alignment, Constraint, Flex, Layout},
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Paragraph, Widget, Wrap},
+};
+use tui_widgets::big_text::{BigText, PixelSize};
+
+/// Just the task being worked on and how long is left of the session
+pub fn render(
+    task: Option<&Task>,
+    time_left: Duration,
+    config: &Config,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let [name_area, details_area, timer_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(2),
+        Constraint::Length(4),
+    ])
+    .flex(Flex::Center)
+    .areas(area);
+    let Some(task) = task else {
+        Paragraph::new("Nothing to work on")
+            .alignment(Alignment::Center)
+            .render(name_area, buf);
+        return;
+    };
+    Paragraph::new(task.name.as_str().bold())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .render(name_area, buf);
+
+    let mut details = vec![format!("Priority {}", task.priority)];
+    if let Some(deadline) = task.deadline {
+        details.push(format!("Due {}", config.format_deadline(&deadline)));
+    }
+    if let Some(estimate) = task.estimate {
+        details.push(format!("~{}", format_estimate(estimate as u64)));
+    }
+    if let Some(energy) = task.energy {
+        details.push(format!("{} energy", energy.label()));
+    }
+    for tag in &task.tags {
+        details.push(format!("#{}", tag));
+    }
+    Paragraph::new(Line::from(details.join("  ")).dim())
+        .alignment(Alignment::Center)
+        .render(details_area, buf);
+
+    let style = if time_left < Duration::zero() {
+        Style::new().fg(Color::Red)
+    } else {
+        Style::new()
+    };
+    BigText::builder()
+        .pixel_size(PixelSize::Quadrant)
+        .style(style)
+        .lines(vec![format_timer(time_left).into()])
+        .centered()
+        .build()
+        .render(timer_area, buf);
+}
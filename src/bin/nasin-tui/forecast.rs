@@ -0,0 +1,49 @@
+use nasin::scheduler::{format_deadline, Forecast};
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+
+pub fn render(forecast: &Forecast, area: Rect, buf: &mut Buffer) {
+    let missed_style = Style::new().fg(Color::Red).bold();
+    let mut lines: Vec<Line> = Vec::new();
+    for step in &forecast.steps {
+        let line = Line::from(format!(
+            "{} {} ({})",
+            step.time.format("%H:%M"),
+            step.name,
+            step.priority
+        ));
+        if forecast
+            .missed_deadlines
+            .iter()
+            .any(|x| x.task_id == step.task_id)
+        {
+            lines.push(line.style(missed_style));
+        } else {
+            lines.push(line);
+        }
+    }
+    if forecast.steps.is_empty() {
+        lines.push(Line::from("Nothing to work on"));
+    }
+    if !forecast.missed_deadlines.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Deadlines missed:".bold()));
+        for missed in &forecast.missed_deadlines {
+            lines.push(
+                Line::from(format!(
+                    "  {} (due {})",
+                    missed.name,
+                    format_deadline(&missed.deadline)
+                ))
+                .style(missed_style),
+            );
+        }
+    }
+    Paragraph::new(lines)
+        .block(Block::bordered().title(" Forecast "))
+        .render(area, buf);
+}
@@ -1,35 +1,122 @@
 mod add;
+mod focus;
+mod forecast;
+mod projects;
+mod settings;
+mod stats;
+mod today;
 
-use std::{cell::RefCell, io};
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    io,
+    time::{Duration, Instant},
+};
 
-use nasin::scheduler::{Task, Tasks};
+use chrono::Local;
+use nasin::agenda::Agenda;
+use nasin::capacity;
+use nasin::config::Config;
+use nasin::keymap::{Action, Feed, Keymap};
+use nasin::notifications::{NotificationSettings, Notifier};
+use nasin::parser;
+use nasin::project::{FairShare, Filter, Project};
+use nasin::scheduler::{format_duration, format_estimate, Changes, Energy, Snooze, Task, Tasks};
+use nasin::stats::Stats;
 use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
-    layout::Constraint,
+    layout::{Constraint, Flex, Layout},
     prelude::{Buffer, Rect},
     style::{Color, Style, Stylize},
     symbols::border,
-    text::Line,
-    widgets::{Block, Cell, Row, Table, Widget},
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, Padding, Paragraph, Row, Table, Widget},
     DefaultTerminal, Frame,
 };
+use tui_widgets::prompts::{FocusState, State, TextPrompt, TextState};
+
+// Something hard to take back, waiting to be confirmed
+enum Destructive {
+    Remove(Vec<Task>),
+    Finish(Task),
+    Change(Vec<u64>, Changes),
+}
+
+// How long the message offering to undo stays up
+const UNDO_SECONDS: u64 = 10;
 
 struct App<'a> {
     tasks: Tasks,
+    config: Config,
     selected: usize,
     exit: bool,
     add_popup_open: bool,
     add_popup: RefCell<add::Popup<'a>>,
+    quick_add_open: bool,
+    quick_add: RefCell<TextState<'a>>,
+    change_open: bool,
+    change: RefCell<TextState<'a>>,
+    snooze_open: bool,
+    snooze: RefCell<TextState<'a>>,
+    deadline_open: bool,
+    deadline: RefCell<TextState<'a>>,
+    rename_open: bool,
+    rename: RefCell<TextState<'a>>,
+    marked: BTreeSet<u64>,
+    // Which project's tasks are shown
+    filter: Filter,
+    // Where the range being selected started
+    visual: Option<usize>,
+    stats_open: bool,
+    forecast_open: bool,
+    today_open: bool,
+    focus_open: bool,
+    settings_open: bool,
+    settings: settings::Settings,
+    keymap: Keymap,
+    help_open: bool,
+    confirming: Option<Destructive>,
+    message: Option<(String, Instant)>,
+    notifier: Notifier,
 }
 
+// How many steps ahead the forecast panel looks
+const FORECAST_STEPS: usize = 16;
+
 impl App<'_> {
     pub fn new() -> Self {
+        let config = Config::load();
         App {
             tasks: Tasks::load(),
+            keymap: Keymap::new(&config.keybindings),
+            help_open: false,
+            confirming: None,
+            message: None,
+            config,
             selected: 0,
             exit: false,
             add_popup_open: false,
             add_popup: RefCell::new(add::Popup::new()),
+            quick_add_open: false,
+            change_open: false,
+            change: RefCell::new(TextState::new().with_focus(FocusState::Focused)),
+            snooze_open: false,
+            snooze: RefCell::new(TextState::new().with_focus(FocusState::Focused)),
+            deadline_open: false,
+            deadline: RefCell::new(TextState::new().with_focus(FocusState::Focused)),
+            rename_open: false,
+            rename: RefCell::new(TextState::new().with_focus(FocusState::Focused)),
+            marked: BTreeSet::new(),
+            filter: Filter::All,
+            visual: None,
+            stats_open: false,
+            forecast_open: false,
+            today_open: false,
+            focus_open: false,
+            settings_open: false,
+            settings: settings::Settings::default(),
+            quick_add: RefCell::new(TextState::new().with_focus(FocusState::Focused)),
+            notifier: Notifier::new(NotificationSettings::load()),
         }
     }
 
@@ -46,24 +133,166 @@ impl App<'_> {
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(key_event)
-            }
-            _ => {}
-        };
+        // Wake up every so often to check for notifications
+        let pressed = event::poll(Duration::from_secs(1))?;
+        // Pick up changes from the GTK app or the CLI before making any,
+        // so they aren't saved over
+        self.tasks.reload();
+        if pressed {
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_key_event(key_event)
+                }
+                _ => {}
+            };
+        }
+        self.tasks.wake(Local::now());
+        self.tasks.update_deadlines(Local::now());
+        self.keep_selection_visible();
+        for notification in self.notifier.poll(&self.tasks, Local::now()) {
+            notification.send_with_notify_send();
+        }
         Ok(())
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) {
-        if self.add_popup_open {
+        let closes = |action| {
+            let back = matches!(key_event.code, KeyCode::Char('q') | KeyCode::Esc);
+            back || self.keymap.is(&key_event, action)
+        };
+        if let Some(action) = self.confirming.take() {
+            if let KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter = key_event.code {
+                self.confirmed(action);
+            }
+        } else if self.help_open {
+            self.help_open = false;
+        } else if self.stats_open {
+            if closes(Action::Stats) {
+                self.stats_open = false;
+            }
+        } else if self.today_open {
+            if closes(Action::Today) {
+                self.today_open = false;
+            }
+        } else if self.focus_open {
+            // Only the keys for the current task work here
+            match self.keymap.feed(&key_event) {
+                Feed::Action(Action::Focus | Action::Quit) => self.focus_open = false,
+                Feed::Action(Action::Step) => self.step(),
+                Feed::Action(Action::Finish) => self.finish(),
+                Feed::Action(Action::Pause) => {
+                    if let Some(task) = self.tasks.tasks.first().filter(|x| !x.paused).cloned() {
+                        self.tasks.toggle_pause(&task);
+                    }
+                }
+                _ => {}
+            }
+        } else if self.settings_open {
+            match key_event.code {
+                _ if closes(Action::Settings) => self.settings_open = false,
+                KeyCode::Char('j') | KeyCode::Down => self.settings.select_down(),
+                KeyCode::Char('k') | KeyCode::Up => self.settings.select_up(),
+                KeyCode::Char('l') | KeyCode::Char('+') | KeyCode::Right => self.adjust_setting(1),
+                KeyCode::Char('h') | KeyCode::Char('-') | KeyCode::Left => self.adjust_setting(-1),
+                _ => {}
+            }
+        } else if self.quick_add_open {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.quick_add_open = false;
+                    self.quick_add.borrow_mut().value_mut().clear();
+                }
+                KeyCode::Enter => {
+                    let parsed = parser::parse(self.quick_add.borrow().value(), Local::now());
+                    if !parsed.name.is_empty() {
+                        let project = parsed.project_in(&mut self.tasks);
+                        let mut task = parsed.into_task_or(self.config.default_priority);
+                        task.project = project;
+                        self.tasks.add(task);
+                    }
+                    self.quick_add.borrow_mut().value_mut().clear();
+                    self.quick_add_open = false;
+                }
+                _ => self.quick_add.borrow_mut().handle_key_event(key_event),
+            }
+        } else if self.change_open {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.change_open = false;
+                    self.change.borrow_mut().value_mut().clear();
+                }
+                KeyCode::Enter => {
+                    let parsed = parser::parse(self.change.borrow().value(), Local::now());
+                    let project = parsed.project_in(&mut self.tasks);
+                    let mut changes = parsed.into_changes();
+                    changes.project = project.map(Some);
+                    if changes != Changes::default() {
+                        self.change_selection(changes);
+                    }
+                    self.change.borrow_mut().value_mut().clear();
+                    self.change_open = false;
+                }
+                _ => self.change.borrow_mut().handle_key_event(key_event),
+            }
+        } else if self.snooze_open {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.snooze_open = false;
+                    self.snooze.borrow_mut().value_mut().clear();
+                }
+                KeyCode::Enter => {
+                    let text = self.snooze.borrow().value().trim().to_string();
+                    self.snooze_selection(&text);
+                    self.snooze.borrow_mut().value_mut().clear();
+                    self.snooze_open = false;
+                }
+                _ => self.snooze.borrow_mut().handle_key_event(key_event),
+            }
+        } else if self.deadline_open {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.deadline_open = false;
+                    self.deadline.borrow_mut().value_mut().clear();
+                }
+                KeyCode::Enter => {
+                    let text = self.deadline.borrow().value().trim().to_string();
+                    self.set_deadline(&text);
+                    self.deadline.borrow_mut().value_mut().clear();
+                    self.deadline_open = false;
+                }
+                _ => self.deadline.borrow_mut().handle_key_event(key_event),
+            }
+        } else if self.rename_open {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.rename_open = false;
+                    self.rename.borrow_mut().value_mut().clear();
+                }
+                KeyCode::Enter => {
+                    let name = self.rename.borrow().value().to_string();
+                    if let Some(id) = self.tasks.tasks.get(self.selected).map(|x| x.id) {
+                        if self.tasks.rename(id, &name) {
+                            self.message =
+                                Some((format!("Renamed to {}", name.trim()), Instant::now()));
+                        }
+                    }
+                    self.rename.borrow_mut().value_mut().clear();
+                    self.rename_open = false;
+                }
+                _ => self.rename.borrow_mut().handle_key_event(key_event),
+            }
+        } else if self.add_popup_open {
             match key_event.code {
                 KeyCode::Esc => {
                     self.add_popup_open = false;
                     self.add_popup.borrow_mut().reset()
-                },
+                }
                 KeyCode::Enter => {
-                    if let Some(task) = self.add_popup.borrow().to_task() {
+                    if let Some(task) = self
+                        .add_popup
+                        .borrow()
+                        .to_task(self.config.default_priority)
+                    {
                         self.tasks.add(task);
                     }
                     self.add_popup.borrow_mut().reset();
@@ -73,120 +302,667 @@ impl App<'_> {
                 KeyCode::BackTab | KeyCode::Up => self.add_popup.borrow_mut().focus_up(),
                 _ => self.add_popup.borrow_mut().handle_key_event(key_event),
             }
-        } else {
-            match key_event.code {
-                KeyCode::Char('q') | KeyCode::Esc => self.exit = true,
-                KeyCode::Char('j') | KeyCode::Down => self.select_down(),
-                KeyCode::Char('k') | KeyCode::Up => self.select_up(),
-                KeyCode::Char('s') => self.step(),
-                KeyCode::Char('f') => self.finish(),
-                KeyCode::Char('p') => self.pause(),
-                KeyCode::Char('d') => self.remove(),
-                KeyCode::Char('a') => self.add_popup_open = true,
-                _ => {}
+        } else if let Feed::Action(action) = self.keymap.feed(&key_event) {
+            self.do_action(action);
+        }
+    }
+
+    fn do_action(&mut self, action: Action) {
+        match action {
+            Action::Down => self.select_down(),
+            Action::Up => self.select_up(),
+            Action::MoveDown => self.move_selected(true),
+            Action::MoveUp => self.move_selected(false),
+            Action::Mark => self.mark(),
+            Action::Visual => {
+                self.visual = match self.visual {
+                    Some(_) => None,
+                    None => Some(self.selected),
+                }
+            }
+            Action::Step => self.step(),
+            Action::WorkOn => self.work_on(),
+            Action::Finish => self.finish(),
+            Action::Pause => self.pause(),
+            Action::Snooze => self.snooze_open = !self.selection().is_empty(),
+            Action::Remove => self.remove(),
+            Action::Undo => self.undo(),
+            Action::Change => self.change_open = !self.selection().is_empty(),
+            Action::Raise => self.shift_priority(true),
+            Action::Lower => self.shift_priority(false),
+            Action::Deadline => self.deadline_open = !self.selection().is_empty(),
+            Action::Rename => {
+                if let Some(task) = self.tasks.tasks.get(self.selected) {
+                    let mut rename = TextState::new()
+                        .with_focus(FocusState::Focused)
+                        .with_value(task.name.clone());
+                    rename.move_end();
+                    *self.rename.borrow_mut() = rename;
+                    self.rename_open = true;
+                }
+            }
+            Action::Project => {
+                self.filter = self.filter.next(&self.tasks);
+                self.keep_selection_visible();
+            }
+            Action::Balance => {
+                let fair_share = self.tasks.fair_share.next();
+                self.tasks.set_fair_share(fair_share);
+                let message = match fair_share {
+                    FairShare::Off => String::from("Going by priority alone"),
+                    share => format!("Sharing time between {}", share.label().to_lowercase()),
+                };
+                self.message = Some((message, Instant::now()));
             }
+            Action::Energy => {
+                // Go through each level, then back to not minding
+                let energy = match self.tasks.energy {
+                    None => Some(Energy::Low),
+                    Some(Energy::Low) => Some(Energy::Medium),
+                    Some(Energy::Medium) => Some(Energy::High),
+                    Some(Energy::High) => None,
+                };
+                self.tasks.set_energy(energy);
+            }
+            Action::Add => self.add_popup_open = true,
+            Action::QuickAdd => self.quick_add_open = true,
+            Action::Stats => self.stats_open = true,
+            Action::Forecast => self.forecast_open = !self.forecast_open,
+            Action::Today => self.today_open = true,
+            Action::Focus => self.focus_open = true,
+            Action::Settings => self.settings_open = true,
+            Action::Help => self.help_open = true,
+            // Let go of the selection before quitting
+            Action::Quit if self.has_selection() => self.clear_selection(),
+            Action::Quit => self.exit = true,
         }
     }
 
     // Move selection down
     fn select_down(&mut self) {
-        let len = self.tasks.tasks.len();
-        self.selected = (self.selected + 1).min(len - 1)
+        let tasks = &self.tasks.tasks;
+        self.selected = (self.selected + 1..tasks.len())
+            .find(|x| self.filter.matches(&tasks[*x]))
+            .unwrap_or(self.selected)
     }
 
-    // Move selection down
+    // Move selection up
     fn select_up(&mut self) {
-        self.selected = self.selected.saturating_sub(1)
+        let tasks = &self.tasks.tasks;
+        self.selected = (0..self.selected)
+            .rev()
+            .find(|x| self.filter.matches(&tasks[*x]))
+            .unwrap_or(self.selected)
+    }
+
+    // Move the selected task past the next one shown, in the queue
+    fn move_selected(&mut self, down: bool) {
+        let tasks = &self.tasks.tasks;
+        let Some(task) = tasks.get(self.selected).cloned() else {
+            return;
+        };
+        let visible = |x: &usize| self.filter.matches(&tasks[*x]);
+        let target = if down {
+            (self.selected + 1..tasks.len()).find(visible)
+        } else {
+            (0..self.selected).rev().find(visible)
+        };
+        if target.is_some_and(|x| self.tasks.move_task(task.id, x)) {
+            self.selected = self
+                .tasks
+                .tasks
+                .iter()
+                .position(|x| x.id == task.id)
+                .unwrap_or(self.selected);
+            self.message = Some((format!("Moved {}", task.name), Instant::now()));
+        }
+    }
+
+    // Move the cursor off tasks hidden by the project filter
+    fn keep_selection_visible(&mut self) {
+        let tasks = &self.tasks.tasks;
+        let visible = |x: &usize| self.filter.matches(&tasks[*x]);
+        if tasks
+            .get(self.selected)
+            .is_none_or(|x| self.filter.matches(x))
+        {
+            return;
+        }
+        self.selected = (self.selected..tasks.len())
+            .find(visible)
+            .or_else(|| (0..self.selected).rev().find(visible))
+            .unwrap_or(self.selected);
     }
 
     // Step the tasks
     fn step(&mut self) {
-        self.tasks.step();
+        let promoted = self.tasks.step();
+        self.start_quantum(promoted);
+    }
+
+    // Work on the selected task instead of the one at the top
+    fn work_on(&mut self) {
+        if let Some(id) = self.tasks.tasks.get(self.selected).map(|x| x.id) {
+            let promoted = self.tasks.work_on(id);
+            self.start_quantum(promoted);
+        }
     }
 
     // Finish the current task
     fn finish(&mut self) {
-        self.tasks.step_and_finish();
+        let Some(task) = self.tasks.tasks.first().filter(|x| !x.paused).cloned() else {
+            return;
+        };
+        if self.config.confirm.finish {
+            self.confirming = Some(Destructive::Finish(task));
+        } else {
+            self.confirmed(Destructive::Finish(task));
+        }
+    }
+
+    // Restart the work timer, telling the user if a task got promoted
+    fn start_quantum(&mut self, promoted: Option<Task>) {
+        self.notifier.start_quantum(Local::now());
+        if let Some(notification) = promoted.and_then(|x| self.notifier.promoted(&x)) {
+            notification.send_with_notify_send();
+        }
+    }
+
+    // Change the selected setting
+    fn adjust_setting(&mut self, by: i64) {
+        self.settings.adjust(&mut self.tasks, by);
+    }
+
+    // Whether any tasks are marked or in a range
+    fn has_selection(&self) -> bool {
+        !self.marked.is_empty() || self.visual.is_some()
     }
 
-    // Toggle a task's paused state
+    fn clear_selection(&mut self) {
+        self.marked.clear();
+        self.visual = None;
+    }
+
+    fn is_selected(&self, i: usize, task: &Task) -> bool {
+        let in_range = self
+            .visual
+            .is_some_and(|x| (x.min(self.selected)..=x.max(self.selected)).contains(&i))
+            && self.filter.matches(task);
+        in_range || self.marked.contains(&task.id)
+    }
+
+    // The tasks to act on: the marked ones and any in the range being
+    // selected, or the one under the cursor if there are none
+    fn selection(&self) -> Vec<Task> {
+        let tasks = &self.tasks.tasks;
+        if !self.has_selection() {
+            return tasks.get(self.selected).cloned().into_iter().collect();
+        }
+        tasks
+            .iter()
+            .enumerate()
+            .filter(|(i, task)| self.is_selected(*i, task))
+            .map(|(_, task)| task.clone())
+            .collect()
+    }
+
+    // Mark or unmark the task under the cursor
+    fn mark(&mut self) {
+        if let Some(task) = self.tasks.tasks.get(self.selected) {
+            if !self.marked.remove(&task.id) {
+                self.marked.insert(task.id);
+            }
+        }
+        self.select_down();
+    }
+
+    // Go ahead, asking first if `confirm` is set
+    fn ask(&mut self, action: Destructive, confirm: bool) {
+        if confirm {
+            self.confirming = Some(action);
+        } else {
+            self.confirmed(action);
+        }
+    }
+
+    // Toggle the paused state of the selected tasks
     fn pause(&mut self) {
-        let task = &self.tasks.tasks[self.selected];
-        self.tasks.toggle_pause(&task.clone());
+        let tasks = self.selection();
+        match tasks.as_slice() {
+            [] => {}
+            [task] if !self.has_selection() => self.tasks.toggle_pause(task),
+            _ => {
+                let changes = Changes {
+                    paused: Some(tasks.iter().any(|x| !x.paused)),
+                    ..Changes::default()
+                };
+                self.change_selection(changes);
+            }
+        }
+    }
+
+    // Snooze the selected tasks until a preset, by its number, or a
+    // date and time written like in quick add
+    fn snooze_selection(&mut self, text: &str) {
+        let now = Local::now();
+        let until = match text.parse::<usize>() {
+            Ok(i) => Snooze::ALL.get(i.wrapping_sub(1)).map(|x| x.until(now)),
+            Err(_) => parser::parse(text, now).deadline,
+        };
+        let Some(until) = until else {
+            return;
+        };
+        let ids: Vec<u64> = self.selection().iter().map(|x| x.id).collect();
+        self.tasks.snooze(&ids, until);
+        self.clear_selection();
+        self.message = Some((
+            format!("Snoozed until {}", self.config.format_deadline(&until)),
+            Instant::now(),
+        ));
     }
 
-    // Remove fa task
+    // Raise or lower the selected tasks by a priority level, keeping
+    // the cursor on the same task
+    fn shift_priority(&mut self, raise: bool) {
+        let tasks = self.selection();
+        let ids: Vec<u64> = tasks.iter().map(|x| x.id).collect();
+        let cursor = self.tasks.tasks.get(self.selected).map(|x| x.id);
+        let changed = if raise {
+            self.tasks.raise_priority(&ids)
+        } else {
+            self.tasks.lower_priority(&ids)
+        };
+        let position = |id: u64| self.tasks.tasks.iter().position(|x| x.id == id);
+        if let Some(index) = cursor.and_then(position) {
+            self.selected = index;
+        }
+        let verb = if raise { "Raised" } else { "Lowered" };
+        let message = match (changed, tasks.as_slice()) {
+            (0, [task]) if task.deadline.is_some() => {
+                format!("{} gets its priority from its deadline", task.name)
+            }
+            (0, _) => return,
+            (1, [task]) => {
+                let priority = self.tasks.find(task.id).map(|x| x.priority);
+                format!(
+                    "{} {} to priority {}",
+                    verb,
+                    task.name,
+                    priority.unwrap_or(0)
+                )
+            }
+            (n, _) => format!("{} {} tasks", verb, n),
+        };
+        self.message = Some((message, Instant::now()));
+    }
+
+    // Set the deadline of the selected tasks from something like
+    // "friday 5pm", or clear it if there's nothing
+    fn set_deadline(&mut self, text: &str) {
+        let deadline = if text.is_empty() {
+            None
+        } else {
+            match parser::parse(text, Local::now()).deadline {
+                Some(deadline) => Some(deadline),
+                None => {
+                    self.message = Some((format!("No date in \"{}\"", text), Instant::now()));
+                    return;
+                }
+            }
+        };
+        self.change_selection(Changes {
+            deadline: Some(deadline),
+            ..Changes::default()
+        });
+    }
+
+    fn change_selection(&mut self, changes: Changes) {
+        let ids: Vec<u64> = self.selection().iter().map(|x| x.id).collect();
+        let confirm = ids.len() > 1 && self.config.confirm.bulk;
+        self.ask(Destructive::Change(ids, changes), confirm);
+    }
+
+    // Remove the selected tasks
     fn remove(&mut self) {
-        let task = &self.tasks.tasks[self.selected];
-        self.tasks.remove(task.clone());
+        let tasks = self.selection();
+        let confirm = match tasks.len() {
+            0 => return,
+            1 => self.config.confirm.remove,
+            _ => self.config.confirm.bulk,
+        };
+        self.ask(Destructive::Remove(tasks), confirm);
+    }
+
+    // Go ahead with a removal, finish or change, offering to undo it
+    fn confirmed(&mut self, action: Destructive) {
+        let message = match action {
+            Destructive::Remove(tasks) => {
+                let ids: Vec<u64> = tasks.iter().map(|x| x.id).collect();
+                self.tasks.remove_all(&ids);
+                self.clear_selection();
+                match tasks.as_slice() {
+                    [task] => format!("Removed {}", task.name),
+                    _ => format!("Removed {} tasks", tasks.len()),
+                }
+            }
+            // The head may have changed while asking, so finish the task
+            // that was asked about
+            Destructive::Finish(task) => {
+                if self.tasks.find(task.id).is_none_or(|x| x.paused) {
+                    return;
+                }
+                let promoted = self.tasks.finish(task.id);
+                self.start_quantum(promoted);
+                format!("Finished {}", task.name)
+            }
+            Destructive::Change(ids, changes) => {
+                self.tasks.change(&ids, &changes);
+                self.clear_selection();
+                format!("Changed {} tasks", ids.len())
+            }
+        };
+        self.selected = self.selected.min(self.tasks.tasks.len().saturating_sub(1));
+        self.message = Some((message, Instant::now()));
+    }
+
+    fn undo(&mut self) {
+        if self.tasks.undo() {
+            self.message = Some((String::from("Undone"), Instant::now()));
+        }
     }
 }
 
-fn task_to_row(task: &Task, highlight: bool) -> Row {
+fn task_to_row<'a>(
+    task: &'a Task,
+    highlight: bool,
+    selected: bool,
+    project: Option<&Project>,
+    config: &Config,
+) -> Row<'a> {
     let highlight_style = Style::new().fg(Color::Black).bg(Color::LightYellow);
-    let paused_str = if task.paused { "[P]" } else { "[ ]" };
-    let deadline_str = if let Some(date) = task.deadline {
-        date.format("%Y-%m-%d").to_string()
-    } else {
-        String::from("-")
+    let selected_style = Style::new().fg(Color::Black).bg(Color::LightBlue);
+    let overdue_style = Style::new().fg(Color::Red).bold();
+    let paused_str = match (task.paused, task.snoozed_until) {
+        (true, Some(_)) => "[z]",
+        (true, None) => "[P]",
+        _ => "[ ]",
+    };
+    let overdue = task.overdue_by(Local::now());
+    let deadline_str = match (task.deadline, overdue) {
+        (Some(date), Some(overdue)) => format!(
+            "{} (overdue by {})",
+            config.format_deadline(&date),
+            format_duration(overdue)
+        ),
+        (Some(date), None) => config.format_deadline(&date),
+        _ => String::from("-"),
     };
+    let mut name = task.name.clone();
+    for tag in &task.tags {
+        name += &format!(" #{}", tag);
+    }
+    if let Some(estimate) = task.estimate {
+        name += &format!(" ~{}", format_estimate(estimate as u64));
+    }
+    if let Some(energy) = task.energy {
+        name += &format!(" @{}", energy.label().to_lowercase());
+    }
+    if let Some(until) = task.snoozed_until {
+        name += &format!(" (until {})", config.format_deadline(&until));
+    }
+    let mut name = Line::from(name);
+    if let Some(project) = project {
+        let color = project.color.parse().unwrap_or(Color::Reset);
+        name.spans
+            .insert(0, Span::styled("● ", Style::new().fg(color)));
+        name.push_span(format!(" +{}", project.name));
+    }
     let row = Row::new(vec![
         Cell::new(paused_str),
-        task.name.clone().into(),
+        name.into(),
         task.priority.to_string().into(),
         deadline_str.into(),
     ]);
     if highlight {
         row.style(highlight_style)
+    } else if selected {
+        row.style(selected_style)
+    } else if overdue.is_some() && !task.paused {
+        row.style(overdue_style)
     } else {
         row
     }
 }
 
+// The keys for everything that can be done from the task list
+fn help_line(keymap: &Keymap) -> Line<'static> {
+    let mut spans = Vec::new();
+    for action in Action::ALL {
+        let keys = keymap.keys(action);
+        if keys.is_empty() {
+            continue;
+        }
+        spans.push(format!(" {} ", action.label()).into());
+        spans.push(format!("<{}>", keys).blue().bold());
+    }
+    spans.push(" ".into());
+    Line::from(spans)
+}
+
+// Centre a box of the given size in `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+// Ask whether to go ahead with removing, finishing or changing tasks
+fn render_confirmation(action: &Destructive, area: Rect, buf: &mut Buffer) {
+    let (title, question) = match action {
+        Destructive::Remove(tasks) => match tasks.as_slice() {
+            [task] => (" Remove Task ", format!("Remove {}?", task.name)),
+            _ => (" Remove Tasks ", format!("Remove {} tasks?", tasks.len())),
+        },
+        Destructive::Finish(task) => (" Finish Task ", format!("Finish {}?", task.name)),
+        Destructive::Change(ids, _) => (" Change Tasks ", format!("Change {} tasks?", ids.len())),
+    };
+    let width = (question.chars().count() as u16 + 4).max(24);
+    let area = centered(area, width, 3);
+    Clear.render(area, buf);
+    Paragraph::new(question)
+        .centered()
+        .block(
+            Block::bordered()
+                .title(Line::from(title.bold()).centered())
+                .title_bottom(
+                    Line::from(vec![
+                        " Yes ".into(),
+                        "<y>".blue().bold(),
+                        " No ".into(),
+                        "<n> ".blue().bold(),
+                    ])
+                    .centered(),
+                ),
+        )
+        .render(area, buf);
+}
+
+// Every key, and anything wrong with the ones from the config
+fn render_help(keymap: &Keymap, area: Rect, buf: &mut Buffer) {
+    let mut lines: Vec<Line> = Action::ALL
+        .iter()
+        .map(|x| {
+            Line::from(vec![
+                format!("{:<14}", x.label()).into(),
+                keymap.keys(*x).blue().bold(),
+            ])
+        })
+        .collect();
+    if !keymap.problems.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Problems with the configured keys:".bold()));
+        for problem in &keymap.problems {
+            lines.push(Line::from(format!("  {}", problem)).red());
+        }
+    }
+    let width = lines.iter().map(|x| x.width()).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let area = centered(area, width, height);
+    Clear.render(area, buf);
+    Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .title(Line::from(" Keys ".bold()).centered())
+                .title_bottom(Line::from(" Close <any key> ").centered())
+                .padding(Padding::horizontal(1)),
+        )
+        .render(area, buf);
+}
+
 impl Widget for &App<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        if self.add_popup_open {
-            let title = Line::from(" Add Task... ".bold());
+        if self.stats_open {
+            let title = Line::from(" Statistics ".bold());
             let instructions = Line::from(vec![
-                " Next ".into(),
-                "<Tab>".blue().bold(),
-                " Previous ".into(),
-                "<S-Tab>".blue().bold(),
-                " Add ".into(),
-                "<Enter>".blue().bold(),
-                " Quit ".into(),
-                "<Esc> ".blue().bold(),
+                " Back ".into(),
+                format!("<{}/Esc> ", self.keymap.keys(Action::Stats))
+                    .blue()
+                    .bold(),
             ]);
             let block = Block::bordered()
                 .title(title.centered())
                 .title_bottom(instructions)
                 .border_set(border::THICK);
-            self.add_popup.borrow_mut().render(block.inner(area), buf);
+            let stats = Stats::compute(&self.tasks.history, Local::now(), 14);
+            stats::render(&stats, block.inner(area), buf);
             block.render(area, buf);
-        } else {
-            let title = Line::from(" Nasin ".bold());
+        } else if self.focus_open {
+            let mut instructions = Vec::new();
+            for action in [Action::Step, Action::Finish, Action::Pause, Action::Focus] {
+                instructions.push(format!(" {} ", action.label()).into());
+                instructions.push(format!("<{}>", self.keymap.keys(action)).blue().bold());
+            }
+            instructions.push(" ".into());
+            let block = Block::bordered()
+                .title(Line::from(" Focus ".bold()).centered())
+                .title_bottom(Line::from(instructions))
+                .border_set(border::THICK);
+            focus::render(
+                self.tasks.tasks.first().filter(|x| !x.paused),
+                self.notifier.time_left(Local::now()),
+                &self.config,
+                block.inner(area),
+                buf,
+            );
+            block.render(area, buf);
+            if let Some(action) = &self.confirming {
+                render_confirmation(action, area, buf);
+            }
+        } else if self.today_open {
+            let instructions = Line::from(vec![
+                " Tasks ".into(),
+                format!("<{}/Esc> ", self.keymap.keys(Action::Today))
+                    .blue()
+                    .bold(),
+            ]);
+            let block = Block::bordered()
+                .title(today::tabs(true).centered())
+                .title_bottom(instructions)
+                .border_set(border::THICK);
+            let quantum_minutes = self.notifier.settings.quantum_minutes;
+            let agenda = Agenda::plan(
+                &self.tasks,
+                Local::now(),
+                self.config.today.steps(quantum_minutes),
+                chrono::Duration::minutes(quantum_minutes as i64),
+            );
+            today::render(&agenda, &self.config, block.inner(area), buf);
+            block.render(area, buf);
+        } else if self.settings_open {
+            let title = Line::from(" Settings ".bold());
             let instructions = Line::from(vec![
                 " Down ".into(),
                 "<j/Down>".blue().bold(),
                 " Up ".into(),
                 "<k/Up>".blue().bold(),
-                " Step ".into(),
-                "<s>".blue().bold(),
-                " Finish ".into(),
-                "<f>".blue().bold(),
-                " Toggle Pause ".into(),
-                "<p>".blue().bold(),
-                " Remove ".into(),
-                "<d>".blue().bold(),
+                " Increase ".into(),
+                "<l/+>".blue().bold(),
+                " Decrease ".into(),
+                "<h/->".blue().bold(),
+                " Back ".into(),
+                format!("<{}/Esc> ", self.keymap.keys(Action::Settings))
+                    .blue()
+                    .bold(),
+            ]);
+            let block = Block::bordered()
+                .title(title.centered())
+                .title_bottom(instructions)
+                .border_set(border::THICK);
+            self.settings.render(&self.tasks, block.inner(area), buf);
+            block.render(area, buf);
+        } else if self.add_popup_open {
+            let title = Line::from(" Add Task... ".bold());
+            let instructions = Line::from(vec![
+                " Next ".into(),
+                "<Tab>".blue().bold(),
+                " Previous ".into(),
+                "<S-Tab>".blue().bold(),
                 " Add ".into(),
-                "<a>".blue().bold(),
+                "<Enter>".blue().bold(),
                 " Quit ".into(),
-                "<q/Esc> ".blue().bold(),
+                "<Esc> ".blue().bold(),
             ]);
             let block = Block::bordered()
                 .title(title.centered())
                 .title_bottom(instructions)
                 .border_set(border::THICK);
+            self.add_popup.borrow_mut().render(block.inner(area), buf);
+            block.render(area, buf);
+        } else {
+            let mut block = Block::bordered()
+                .title(today::tabs(false).centered())
+                .title_bottom(help_line(&self.keymap))
+                .border_set(border::THICK);
+            if !self.keymap.problems.is_empty() {
+                block = block.title(
+                    Line::from(format!(
+                        " {} problems with keys, see <{}> ",
+                        self.keymap.problems.len(),
+                        self.keymap.keys(Action::Help)
+                    ))
+                    .red()
+                    .right_aligned(),
+                );
+            }
+            if let Some((message, time)) = &self.message {
+                if time.elapsed().as_secs() < UNDO_SECONDS {
+                    let mut line = Line::from(format!(" {}", message));
+                    if self.tasks.can_undo() {
+                        line.push_span(" Undo ");
+                        line.push_span(
+                            format!("<{}> ", self.keymap.keys(Action::Undo))
+                                .blue()
+                                .bold(),
+                        );
+                    }
+                    block = block.title(line.left_aligned());
+                }
+            }
+            let pending = self.keymap.pending();
+            if !pending.is_empty() {
+                block = block.title_bottom(Line::from(format!(" {} ", pending)).right_aligned());
+            }
+            if let Some(energy) = self.tasks.energy {
+                block = block.title(
+                    Line::from(format!(" {} energy ", energy.label()))
+                        .yellow()
+                        .right_aligned(),
+                );
+            }
             let header = Row::new(vec![
                 "P?".bold(),
                 "Name".bold(),
@@ -198,8 +974,18 @@ impl Widget for &App<'_> {
                 .tasks
                 .iter()
                 .enumerate()
-                .map(|(i, t)| task_to_row(t, i == self.selected));
-            Table::new(
+                .filter(|(_, t)| self.filter.matches(t))
+                .map(|(i, t)| {
+                    let project = t.project.and_then(|x| self.tasks.project(x));
+                    task_to_row(
+                        t,
+                        i == self.selected,
+                        self.is_selected(i, t),
+                        project,
+                        &self.config,
+                    )
+                });
+            let table = Table::new(
                 rows,
                 vec![
                     Constraint::Length(3),
@@ -209,8 +995,99 @@ impl Widget for &App<'_> {
                 ],
             )
             .header(header)
-            .block(block)
-            .render(area, buf);
+            .block(block);
+            let mut table_area = area;
+            let prompt = if self.quick_add_open {
+                Some((String::from(" Quick Add "), " Add ", &self.quick_add))
+            } else if self.change_open {
+                Some((
+                    String::from(" Change Selected: !priority #tags deadline "),
+                    " Change ",
+                    &self.change,
+                ))
+            } else if self.deadline_open {
+                Some((
+                    String::from(" Deadline: like friday 5pm, or nothing to clear it "),
+                    " Set ",
+                    &self.deadline,
+                ))
+            } else if self.rename_open {
+                Some((String::from(" Rename "), " Rename ", &self.rename))
+            } else if self.snooze_open {
+                let presets: Vec<String> = Snooze::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| format!("{} {}", i + 1, x.label()))
+                    .collect();
+                Some((
+                    format!(" Snooze Until: {}, or a date ", presets.join(", ")),
+                    " Snooze ",
+                    &self.snooze,
+                ))
+            } else {
+                None
+            };
+            if let Some((title, verb, state)) = prompt {
+                let [rest, prompt_area] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(area);
+                table_area = rest;
+                let prompt_block =
+                    Block::bordered()
+                        .title(title.as_str())
+                        .title_bottom(Line::from(vec![
+                            verb.into(),
+                            "<Enter>".blue().bold(),
+                            " Cancel ".into(),
+                            "<Esc> ".blue().bold(),
+                        ]));
+                ratatui::widgets::StatefulWidget::render(
+                    TextPrompt::new("".into()),
+                    prompt_block.inner(prompt_area),
+                    buf,
+                    &mut state.borrow_mut(),
+                );
+                prompt_block.render(prompt_area, buf);
+            }
+            let at_risk = capacity::check(&self.tasks, Local::now());
+            if !at_risk.is_empty() {
+                let [warning_area, rest] =
+                    Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+                        .areas(table_area);
+                table_area = rest;
+                let warnings: Vec<String> = at_risk
+                    .iter()
+                    .map(|x| format!("{} ({})", x.name, x.describe()))
+                    .collect();
+                Paragraph::new(format!(" At risk: {}", warnings.join(", ")))
+                    .red()
+                    .bold()
+                    .render(warning_area, buf);
+            }
+            if !self.tasks.projects.is_empty() {
+                let [projects_area, rest] =
+                    Layout::horizontal([Constraint::Length(24), Constraint::Fill(1)])
+                        .areas(table_area);
+                table_area = rest;
+                projects::render(&self.tasks, self.filter, projects_area, buf);
+            }
+            if self.forecast_open {
+                let [rest, forecast_area] =
+                    Layout::horizontal([Constraint::Fill(2), Constraint::Fill(1)])
+                        .areas(table_area);
+                table_area = rest;
+                let forecast = self.tasks.simulate_from(
+                    FORECAST_STEPS,
+                    Local::now(),
+                    chrono::Duration::minutes(self.notifier.settings.quantum_minutes as i64),
+                );
+                forecast::render(&forecast, forecast_area, buf);
+            }
+            table.render(table_area, buf);
+            if let Some(action) = &self.confirming {
+                render_confirmation(action, area, buf);
+            } else if self.help_open {
+                render_help(&self.keymap, area, buf);
+            }
         }
     }
 }
@@ -0,0 +1,43 @@
+use nasin::project::{FairShare, Filter};
+use nasin::scheduler::Tasks;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+/// The projects down the side, with the one being shown picked out
+pub fn render(tasks: &Tasks, filter: Filter, area: Rect, buf: &mut Buffer) {
+    let line = |this: Filter, dot: Option<Span<'static>>, name: &str| {
+        let mut line = Line::from(if this == filter { "> " } else { "  " });
+        line.extend(dot);
+        line.push_span(name.to_string());
+        if this == filter {
+            line.bold()
+        } else {
+            line
+        }
+    };
+    let mut lines = vec![line(Filter::All, None, "All tasks")];
+    for project in &tasks.projects {
+        let color = project.color.parse().unwrap_or(Color::Reset);
+        let dot = Span::styled("● ", Style::new().fg(color));
+        let mut project_line = line(Filter::Project(project.id), Some(dot), &project.name);
+        if tasks.fair_share == FairShare::Projects && project.weight > 1 {
+            project_line.push_span(Span::raw(format!(" ×{}", project.weight)).dim());
+        }
+        if project.archived {
+            project_line = project_line.dim();
+        }
+        lines.push(project_line);
+    }
+    lines.push(line(Filter::NoProject, None, "No project"));
+    let title = match tasks.fair_share {
+        FairShare::Off => String::from(" Projects "),
+        share => format!(" Projects (sharing {}) ", share.label().to_lowercase()),
+    };
+    Paragraph::new(lines)
+        .block(Block::bordered().title(title))
+        .render(area, buf);
+}
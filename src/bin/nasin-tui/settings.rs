@@ -0,0 +1,128 @@
+use nasin::scheduler::Tasks;
+use ratatui::{
+    layout::Constraint,
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    widgets::{Row, Table, Widget},
+};
+
+// Name, description, and largest value of each setting
+const FIELDS: [(&str, &str, u32); 6] = [
+    (
+        "Aging increment",
+        "How much older each waiting task gets per step",
+        100,
+    ),
+    (
+        "Promotion threshold",
+        "How old a task has to be before it can be promoted",
+        1000,
+    ),
+    (
+        "Promotions per step",
+        "How many of the oldest tasks are promoted each step",
+        100,
+    ),
+    (
+        "Promotion amount",
+        "How many priority levels each promotion is worth",
+        u8::MAX as u32,
+    ),
+    (
+        "Maximum promotion",
+        "How far above its usual priority a task can be promoted",
+        u8::MAX as u32,
+    ),
+    (
+        "Overdue goes first",
+        "Put overdue tasks ahead of everything else, not at priority 1",
+        1,
+    ),
+];
+
+// The one setting that is on or off, from the deadline policy
+const OVERDUE_PREEMPTS: usize = 5;
+
+fn get(tasks: &Tasks, field: usize) -> u32 {
+    let policy = &tasks.aging_policy;
+    match field {
+        0 => policy.increment,
+        1 => policy.threshold,
+        2 => policy.boosted_per_step,
+        3 => policy.boost as u32,
+        4 => policy.max_boost as u32,
+        _ => tasks.deadline_policy.overdue_preempts as u32,
+    }
+}
+
+fn set(tasks: &mut Tasks, field: usize, value: u32) {
+    if field == OVERDUE_PREEMPTS {
+        let mut policy = tasks.deadline_policy.clone();
+        policy.overdue_preempts = value == 1;
+        if policy != tasks.deadline_policy {
+            tasks.set_deadline_policy(policy);
+        }
+        return;
+    }
+    let mut policy = tasks.aging_policy;
+    match field {
+        0 => policy.increment = value,
+        1 => policy.threshold = value,
+        2 => policy.boosted_per_step = value,
+        3 => policy.boost = value as u8,
+        _ => policy.max_boost = value as u8,
+    }
+    tasks.set_aging_policy(policy);
+}
+
+#[derive(Default)]
+pub struct Settings {
+    selected: usize,
+}
+
+impl Settings {
+    pub fn select_down(&mut self) {
+        self.selected = (self.selected + 1).min(FIELDS.len() - 1)
+    }
+
+    pub fn select_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1)
+    }
+
+    /// Change the selected setting by `by`, and save
+    pub fn adjust(&self, tasks: &mut Tasks, by: i64) {
+        let max = FIELDS[self.selected].2 as i64;
+        let value = (get(tasks, self.selected) as i64 + by).clamp(0, max);
+        set(tasks, self.selected, value as u32);
+    }
+
+    pub fn render(&self, tasks: &Tasks, area: Rect, buf: &mut Buffer) {
+        let highlight_style = Style::new().fg(Color::Black).bg(Color::LightYellow);
+        let rows = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(i, (name, description, _))| {
+                let value = match (i, get(tasks, i)) {
+                    (OVERDUE_PREEMPTS, 1) => String::from("On"),
+                    (OVERDUE_PREEMPTS, _) => String::from("Off"),
+                    (_, value) => value.to_string(),
+                };
+                let row = Row::new(vec![name.to_string(), value, description.to_string()]);
+                if i == self.selected {
+                    row.style(highlight_style)
+                } else {
+                    row
+                }
+            });
+        Table::new(
+            rows,
+            vec![
+                Constraint::Length(20),
+                Constraint::Length(6),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(vec!["Setting".bold(), "Value".bold(), "".into()]))
+        .render(area, buf);
+    }
+}
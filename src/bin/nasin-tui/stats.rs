@@ -0,0 +1,92 @@
+use nasin::scheduler::format_duration;
+use nasin::stats::Stats;
+use ratatui::{
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Sparkline, Widget},
+};
+
+pub fn render(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let [chart_area, sparkline_area, text_area] = Layout::vertical([
+        Constraint::Length(10),
+        Constraint::Length(5),
+        Constraint::Fill(1),
+    ])
+    .areas(area);
+
+    let bars: Vec<Bar> = stats
+        .completions_per_day
+        .iter()
+        .map(|(date, count)| {
+            Bar::default()
+                .value(*count as u64)
+                .label(Line::from(date.format("%d").to_string()))
+        })
+        .collect();
+    BarChart::default()
+        .block(Block::bordered().title(format!(
+            " Finished per Day ({} total) ",
+            stats.total_completions()
+        )))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(3)
+        .bar_gap(1)
+        .bar_style(Style::new().fg(Color::Blue))
+        .render(chart_area, buf);
+
+    // Only the newest runs fit, inside the border
+    let fits = sparkline_area.width.saturating_sub(2) as usize;
+    let waits = &stats.recent_waits[stats.recent_waits.len().saturating_sub(fits)..];
+    Sparkline::default()
+        .block(Block::bordered().title(" Steps Waited Before Being Worked On "))
+        .data(waits)
+        .style(Style::new().fg(Color::Yellow))
+        .render(sparkline_area, buf);
+
+    let mut lines = vec![Line::from(vec![
+        "Average wait: ".bold(),
+        stats
+            .average_wait
+            .map(format_duration)
+            .unwrap_or_else(|| String::from("-"))
+            .into(),
+    ])];
+    if !stats.time_per_tag.is_empty() {
+        lines.push(Line::from("Time per tag:".bold()));
+        for (tag, time) in &stats.time_per_tag {
+            lines.push(Line::from(format!(
+                "  #{}: {}",
+                tag,
+                format_duration(*time)
+            )));
+        }
+    }
+    if !stats.time_per_priority.is_empty() {
+        lines.push(Line::from("Time per priority:".bold()));
+        for (priority, time) in &stats.time_per_priority {
+            lines.push(Line::from(format!(
+                "  {}: {}",
+                priority,
+                format_duration(*time)
+            )));
+        }
+    }
+    if !stats.starvation.is_empty() {
+        lines.push(Line::from("Starvation:".bold()));
+        for level in &stats.starvation {
+            lines.push(Line::from(format!(
+                "  Priority {}: {} runs, {:.1} steps waited on average, {} at most, {} promotions",
+                level.priority,
+                level.runs,
+                level.average_steps_waited,
+                level.max_steps_waited,
+                level.promotions
+            )));
+        }
+    }
+    Paragraph::new(lines)
+        .block(Block::bordered())
+        .render(text_area, buf);
+}
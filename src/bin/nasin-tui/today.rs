@@ -0,0 +1,49 @@
+use nasin::agenda::Agenda;
+use nasin::config::Config;
+use ratatui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Paragraph, Widget},
+};
+
+/// The tabs at the top of the screen, with the open one picked out
+pub fn tabs(today_open: bool) -> Line<'static> {
+    let picked = Style::new().bold().reversed();
+    let (tasks, today) = if today_open {
+        (Style::new(), picked)
+    } else {
+        (picked, Style::new())
+    };
+    Line::from(vec![
+        " ".into(),
+        Span::styled(" Tasks ", tasks),
+        " │ ".into(),
+        Span::styled(" Today ", today),
+        " ".into(),
+    ])
+}
+
+pub fn render(agenda: &Agenda, config: &Config, area: Rect, buf: &mut Buffer) {
+    let overdue_style = Style::new().fg(Color::Red).bold();
+    let mut lines: Vec<Line> = Vec::new();
+    let sections = agenda.sections(config);
+    if sections.is_empty() {
+        lines.push(Line::from("Nothing to do today"));
+    }
+    for (title, entries) in sections {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(format!("{}:", title).bold()));
+        for entry in entries {
+            let line = Line::from(format!("  {} ({})", entry.name, entry.detail));
+            if title == "Overdue" {
+                lines.push(line.style(overdue_style));
+            } else {
+                lines.push(line);
+            }
+        }
+    }
+    Paragraph::new(lines).render(area, buf);
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::resolve_local;
+
+// Don't look further ahead than this for working time, so deadlines
+// years away don't take forever
+const MAX_DAYS: u64 = 3660;
+
+/// When the user is available to work, so deadlines can be measured
+/// in the time actually left to work on them
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Calendar {
+    pub working_days: Vec<Weekday>,
+    /// When the working day starts
+    pub start: NaiveTime,
+    /// When the working day ends, which should be after it starts
+    pub end: NaiveTime,
+    /// Days off, even if they fall on a working day
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar {
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            holidays: Vec::new(),
+        }
+    }
+}
+
+impl Calendar {
+    /// Whether any time counts as working time, going by the hours and
+    /// days but not the holidays
+    pub fn has_working_hours(&self) -> bool {
+        self.start < self.end && !self.working_days.is_empty()
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Minutes of working time between `from` and `to`
+    pub fn working_minutes<Tz: TimeZone>(&self, from: &DateTime<Tz>, to: &DateTime<Tz>) -> i64 {
+        if to <= from || self.end <= self.start {
+            return 0;
+        }
+        let tz = from.timezone();
+        let last = to.with_timezone(&tz).date_naive();
+        let mut date = from.date_naive();
+        let mut minutes = 0;
+        for _ in 0..MAX_DAYS {
+            if date > last {
+                break;
+            }
+            if self.is_working_day(date) {
+                let start = resolve_local(&tz, date.and_time(self.start));
+                let end = resolve_local(&tz, date.and_time(self.end));
+                if let (Some(start), Some(end)) = (start, end) {
+                    let start = start.max(from.clone());
+                    let end = end.min(to.clone());
+                    if start < end {
+                        minutes += end.signed_duration_since(start).num_minutes();
+                    }
+                }
+            }
+            date = date + Days::new(1);
+        }
+        minutes
+    }
+}
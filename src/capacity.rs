@@ -0,0 +1,56 @@
+use chrono::{DateTime, Local};
+
+use crate::scheduler::{format_estimate, Tasks};
+
+/// A task that can't be finished by its deadline, even if everything
+/// is worked on in order of deadline from now on
+#[derive(Clone, PartialEq, Debug)]
+pub struct AtRisk {
+    pub task_id: u64,
+    pub name: String,
+    pub deadline: DateTime<Local>,
+    /// Minutes of work due by the deadline, this task's included
+    pub work_due: u64,
+    /// Minutes there are to do it in
+    pub available: u64,
+}
+
+impl AtRisk {
+    /// How far over the time available the work is
+    pub fn describe(&self) -> String {
+        format!(
+            "{} of work due with {} to do it in",
+            format_estimate(self.work_due),
+            format_estimate(self.available)
+        )
+    }
+}
+
+/// Check whether the work left fits before the deadlines, with an
+/// earliest deadline first test. Only tasks with both a deadline and an
+/// estimate are counted, and the time available is working time if the
+/// list has a calendar.
+pub fn check(tasks: &Tasks, now: DateTime<Local>) -> Vec<AtRisk> {
+    let mut due: Vec<_> = tasks
+        .tasks
+        .iter()
+        .filter_map(|x| Some((x, x.deadline?, x.estimate?)))
+        .collect();
+    due.sort_by_key(|x| x.1);
+    let mut at_risk = Vec::new();
+    let mut work_due: u64 = 0;
+    for (task, deadline, estimate) in due {
+        work_due += estimate as u64;
+        let available = tasks.deadline_policy.minutes_left(&deadline, &now) as u64;
+        if work_due > available {
+            at_risk.push(AtRisk {
+                task_id: task.id,
+                name: task.name.clone(),
+                deadline,
+                work_due,
+                available,
+            });
+        }
+    }
+    at_risk
+}
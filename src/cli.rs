@@ -0,0 +1,99 @@
+use chrono::{Duration, Local};
+use gtk::glib::ExitCode;
+use nasin::agenda::Agenda;
+use nasin::capacity;
+use nasin::config::Config;
+use nasin::notifications::NotificationSettings;
+use nasin::parser;
+use nasin::scheduler::{format_estimate, Tasks};
+
+// Add a task from a line of quick-add text
+fn add(args: &[String]) -> ExitCode {
+    let parsed = parser::parse(&args.join(" "), Local::now());
+    if parsed.name.is_empty() {
+        eprintln!("usage: nasin add <task description>");
+        return ExitCode::FAILURE;
+    }
+    let config = Config::load();
+    let mut tasks = Tasks::load();
+    let project = parsed.project_in(&mut tasks);
+    let mut task = parsed.into_task_or(config.default_priority);
+    task.project = project;
+    let mut message = format!("Added {} (priority {}", task.name, task.priority);
+    if let Some(date) = task.deadline {
+        message += &format!(", due {}", config.format_deadline(&date));
+    }
+    if let Some(recurrence) = task.recurrence {
+        message += &format!(", repeats {}", recurrence.describe());
+    }
+    if let Some(estimate) = task.estimate {
+        message += &format!(", {} of work", format_estimate(estimate as u64));
+    }
+    if let Some(start) = task.snoozed_until {
+        message += &format!(", starts {}", config.format_deadline(&start));
+    }
+    if let Some(energy) = task.energy {
+        message += &format!(", {} energy", energy.label().to_lowercase());
+    }
+    if let Some(project) = project.and_then(|x| tasks.project(x)) {
+        message += &format!(", in {}", project.name);
+    }
+    message += ")";
+    tasks.add(task);
+    println!("{}", message);
+    ExitCode::SUCCESS
+}
+
+// Check that the work left fits before its deadlines, failing if not
+fn check() -> ExitCode {
+    let config = Config::load();
+    let at_risk = capacity::check(&Tasks::load(), Local::now());
+    for task in &at_risk {
+        println!(
+            "{} (due {}): {}",
+            task.name,
+            config.format_deadline(&task.deadline),
+            task.describe()
+        );
+    }
+    if at_risk.is_empty() {
+        println!("Everything fits before its deadline");
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// Print the plan for today, as Markdown if asked
+fn today(args: &[String]) -> ExitCode {
+    let markdown = match args {
+        [] => false,
+        [flag] if flag == "--markdown" => true,
+        _ => {
+            eprintln!("usage: nasin today [--markdown]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let config = Config::load();
+    let quantum_minutes = NotificationSettings::load().quantum_minutes;
+    let agenda = Agenda::plan(
+        &Tasks::load(),
+        Local::now(),
+        config.today.steps(quantum_minutes),
+        Duration::minutes(quantum_minutes as i64),
+    );
+    print!("{}", agenda.to_text(&config, markdown));
+    ExitCode::SUCCESS
+}
+
+/// Run a command line subcommand, if one was given. Returns `None` when
+/// the arguments are meant for the GTK app instead.
+pub fn run(args: &[String]) -> Option<ExitCode> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "add" => Some(add(rest)),
+        "check" => Some(check()),
+        "today" => Some(today(rest)),
+        _ => None,
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::format_deadline_with;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_TIME_FORMAT: &str = "%H:%M";
+
+/// Which actions ask before going ahead
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    pub remove: bool,
+    pub finish: bool,
+    /// Anything done to more than one task at once
+    pub bulk: bool,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Confirmations {
+            remove: true,
+            finish: false,
+            bulk: true,
+        }
+    }
+}
+
+/// How much of the day the Today view plans for
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayPlan {
+    /// Hours of work in a day
+    pub hours: u32,
+    /// Plan this many steps instead of filling the hours
+    pub steps: Option<u32>,
+}
+
+impl Default for DayPlan {
+    fn default() -> Self {
+        DayPlan {
+            hours: 6,
+            steps: None,
+        }
+    }
+}
+
+impl DayPlan {
+    /// How many steps there's time for in a day, given how long each
+    /// one is
+    pub fn steps(&self, quantum_minutes: u32) -> usize {
+        match self.steps {
+            Some(steps) => steps as usize,
+            None => (self.hours * 60 / quantum_minutes.max(1)) as usize,
+        }
+    }
+}
+
+/// Settings shared by both front-ends, kept in
+/// `$XDG_CONFIG_HOME/nasin/config.toml`
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Where the task list is kept, instead of the user's data directory
+    pub data_dir: Option<PathBuf>,
+    /// How dates are shown, in `strftime` format
+    pub date_format: String,
+    /// How times of day are shown, in `strftime` format
+    pub time_format: String,
+    /// Priority for new tasks that aren't given one
+    pub default_priority: u8,
+    pub confirm: Confirmations,
+    pub today: DayPlan,
+    /// Keys for the TUI, by the name of the action they do. Each one is
+    /// a key like `ctrl-d`, or a sequence of them like `g g`.
+    pub keybindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: None,
+            date_format: String::from(DEFAULT_DATE_FORMAT),
+            time_format: String::from(DEFAULT_TIME_FORMAT),
+            default_priority: 1,
+            confirm: Confirmations::default(),
+            today: DayPlan::default(),
+            keybindings: BTreeMap::new(),
+        }
+    }
+}
+
+/// Whether `format` can be used to format dates without panicking
+pub fn is_valid_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|x| x != Item::Error)
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        let mut path = dirs::config_dir().unwrap();
+        path.push("nasin");
+        path.push("config");
+        path.set_extension("toml");
+        path
+    }
+
+    /// Load the config file, falling back to the defaults if it's
+    /// missing or broken
+    pub fn load() -> Self {
+        let Ok(text) = std::fs::read_to_string(Self::path()) else {
+            return Config::default();
+        };
+        match Self::from_toml(&text) {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!("Ignoring {}: {}", Self::path().display(), error);
+                Config::default()
+            }
+        }
+    }
+
+    /// Read a config, using the defaults for anything that's missing or
+    /// can't be used
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        let mut config: Config = toml::from_str(text)?;
+        if !is_valid_format(&config.date_format) {
+            config.date_format = String::from(DEFAULT_DATE_FORMAT);
+        }
+        if !is_valid_format(&config.time_format) {
+            config.time_format = String::from(DEFAULT_TIME_FORMAT);
+        }
+        config.default_priority = config.default_priority.max(1);
+        Ok(config)
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, toml::to_string_pretty(self).unwrap()).unwrap();
+    }
+
+    /// The directory the task list and other data is kept in
+    pub fn data_dir(&self) -> PathBuf {
+        self.data_dir.clone().unwrap_or_else(|| {
+            let mut path = dirs::data_dir().unwrap();
+            path.push("nasin");
+            path
+        })
+    }
+
+    /// Show a deadline in the configured format, leaving out the time
+    /// for ones that are just a date
+    pub fn format_deadline(&self, deadline: &DateTime<Local>) -> String {
+        format_deadline_with(deadline, &self.date_format, &self.time_format)
+    }
+}
@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use chrono::Local;
+use gtk::{glib, Align, Button, Label, Orientation};
+use libadwaita::prelude::*;
+use libadwaita::{Application, ApplicationWindow, HeaderBar};
+use nasin::notifications::Notifier;
+use nasin::scheduler::{format_timer, Tasks};
+
+use crate::build_subtitle;
+use crate::settings::Settings;
+
+/// Show a small window with only the task at the head of the queue,
+/// to keep on top of other windows while working on it
+pub fn show_focus(
+    app: &Application,
+    tasks: Rc<RefCell<Tasks>>,
+    notifier: Rc<RefCell<Notifier>>,
+    settings: Rc<Settings>,
+) -> ApplicationWindow {
+    let name_label = Label::builder()
+        .wrap(true)
+        .justify(gtk::Justification::Center)
+        .css_classes(vec!["title-1"])
+        .build();
+    let details_label = Label::builder()
+        .wrap(true)
+        .justify(gtk::Justification::Center)
+        .css_classes(vec!["dim-label"])
+        .build();
+    let timer_label = Label::builder()
+        .css_classes(vec!["title-2", "numeric"])
+        .build();
+
+    let step_button = Button::builder()
+        .label("Step")
+        .action_name("app.step")
+        .css_classes(vec!["pill"])
+        .build();
+    let finish_button = Button::builder()
+        .label("Finish")
+        .action_name("app.finish")
+        .css_classes(vec!["pill", "suggested-action"])
+        .build();
+    let pause_button = Button::builder()
+        .label("Pause")
+        .action_name("app.toggle-pause")
+        .css_classes(vec!["pill"])
+        .build();
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(12)
+        .homogeneous(true)
+        .halign(Align::Center)
+        .build();
+    buttons.append(&step_button);
+    buttons.append(&finish_button);
+    buttons.append(&pause_button);
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(12)
+        .margin_bottom(24)
+        .margin_start(24)
+        .margin_end(24)
+        .valign(Align::Center)
+        .vexpand(true)
+        .build();
+    content.append(&name_label);
+    content.append(&details_label);
+    content.append(&timer_label);
+    content.append(&buttons);
+
+    let holder_box = gtk::Box::new(Orientation::Vertical, 0);
+    holder_box.append(&HeaderBar::builder().css_classes(vec!["flat"]).build());
+    holder_box.append(&content);
+
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .title("Focus")
+        .default_width(360)
+        .default_height(260)
+        .content(&holder_box)
+        .build();
+
+    // Follow the head of the queue and the timer, until the window is
+    // closed
+    let update = move || {
+        let tasks = tasks.borrow();
+        match tasks.tasks.first().filter(|x| !x.paused) {
+            Some(task) => {
+                name_label.set_label(&task.name);
+                details_label.set_label(&build_subtitle(task, &settings.config()));
+                pause_button.set_action_target_value(Some(&task.id.to_variant()));
+                pause_button.set_sensitive(true);
+                finish_button.set_sensitive(true);
+            }
+            None => {
+                name_label.set_label("Nothing to work on");
+                details_label.set_label("");
+                pause_button.set_sensitive(false);
+                finish_button.set_sensitive(false);
+            }
+        }
+        let left = notifier.borrow().time_left(Local::now());
+        timer_label.set_label(&format_timer(left));
+        if left < chrono::Duration::zero() {
+            timer_label.add_css_class("error");
+        } else {
+            timer_label.remove_css_class("error");
+        }
+    };
+    update();
+    glib::timeout_add_seconds_local(
+        1,
+        glib::clone!(@weak window => @default-return glib::ControlFlow::Break, move || {
+            if !window.is_visible() {
+                return glib::ControlFlow::Break;
+            }
+            update();
+            glib::ControlFlow::Continue
+        }),
+    );
+
+    window.present();
+    window
+}
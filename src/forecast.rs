@@ -0,0 +1,98 @@
+use chrono::{Duration, Local};
+use gtk::{glib, Orientation, ScrolledWindow};
+use libadwaita::prelude::*;
+use libadwaita::{ActionRow, ApplicationWindow, HeaderBar, PreferencesGroup};
+use nasin::scheduler::{format_deadline, Tasks};
+
+// How many steps ahead the forecast looks
+const FORECAST_STEPS: usize = 24;
+
+/// Show a window with the order the scheduler will work through the
+/// tasks, one step every `quantum_minutes`, and any deadlines it misses
+pub fn show_forecast(parent: &ApplicationWindow, tasks: &Tasks, quantum_minutes: u32) {
+    let forecast = tasks.simulate_from(
+        FORECAST_STEPS,
+        Local::now(),
+        Duration::minutes(quantum_minutes as i64),
+    );
+
+    let missed_group = PreferencesGroup::builder()
+        .title("Deadlines Missed")
+        .description("These tasks will not be worked on before they are due")
+        .build();
+    for missed in &forecast.missed_deadlines {
+        let worked_on = match missed.first_worked_on {
+            Some(time) => format!("first worked on at {}", time.format("%H:%M")),
+            None => String::from("not worked on in the forecast"),
+        };
+        missed_group.add(
+            &ActionRow::builder()
+                .title(glib::markup_escape_text(&missed.name))
+                .subtitle(format!(
+                    "Due {}, {}",
+                    format_deadline(&missed.deadline),
+                    worked_on
+                ))
+                .css_classes(vec!["error"])
+                .build(),
+        );
+    }
+
+    let timeline_group = PreferencesGroup::builder()
+        .title("Timeline")
+        .description(format!("Assuming a step every {} minutes", quantum_minutes))
+        .build();
+    for (i, step) in forecast.steps.iter().enumerate() {
+        let row = ActionRow::builder()
+            .title(glib::markup_escape_text(&step.name))
+            .subtitle(format!(
+                "Step {} at {}, priority {}",
+                i + 1,
+                step.time.format("%H:%M"),
+                step.priority
+            ))
+            .build();
+        if forecast
+            .missed_deadlines
+            .iter()
+            .any(|x| x.task_id == step.task_id)
+        {
+            row.add_css_class("error");
+        }
+        timeline_group.add(&row);
+    }
+    if forecast.steps.is_empty() {
+        timeline_group.add(&ActionRow::builder().title("Nothing to work on").build());
+    }
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(24)
+        .margin_top(24)
+        .margin_bottom(24)
+        .margin_start(24)
+        .margin_end(24)
+        .build();
+    if !forecast.missed_deadlines.is_empty() {
+        content.append(&missed_group);
+    }
+    content.append(&timeline_group);
+
+    let holder_box = gtk::Box::new(Orientation::Vertical, 0);
+    holder_box.append(&HeaderBar::new());
+    holder_box.append(
+        &ScrolledWindow::builder()
+            .child(&content)
+            .vexpand(true)
+            .min_content_height(500)
+            .build(),
+    );
+
+    let window = ApplicationWindow::builder()
+        .title("Forecast")
+        .transient_for(parent)
+        .default_width(480)
+        .content(&holder_box)
+        .build();
+    window.present();
+}
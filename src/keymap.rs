@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something a key in the task list can do
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Down,
+    Up,
+    MoveDown,
+    MoveUp,
+    Mark,
+    Visual,
+    Step,
+    WorkOn,
+    Finish,
+    Pause,
+    Snooze,
+    Remove,
+    Undo,
+    Change,
+    Raise,
+    Lower,
+    Deadline,
+    Rename,
+    Project,
+    Balance,
+    Energy,
+    Add,
+    QuickAdd,
+    Stats,
+    Forecast,
+    Today,
+    Focus,
+    Settings,
+    Help,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 30] = [
+        Action::Down,
+        Action::Up,
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::Mark,
+        Action::Visual,
+        Action::Step,
+        Action::WorkOn,
+        Action::Finish,
+        Action::Pause,
+        Action::Snooze,
+        Action::Remove,
+        Action::Undo,
+        Action::Change,
+        Action::Raise,
+        Action::Lower,
+        Action::Deadline,
+        Action::Rename,
+        Action::Project,
+        Action::Balance,
+        Action::Energy,
+        Action::Add,
+        Action::QuickAdd,
+        Action::Stats,
+        Action::Forecast,
+        Action::Today,
+        Action::Focus,
+        Action::Settings,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Name used for the action in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::MoveDown => "move_down",
+            Action::MoveUp => "move_up",
+            Action::Mark => "mark",
+            Action::Visual => "visual",
+            Action::Step => "step",
+            Action::WorkOn => "work_on",
+            Action::Finish => "finish",
+            Action::Pause => "pause",
+            Action::Snooze => "snooze",
+            Action::Remove => "remove",
+            Action::Undo => "undo",
+            Action::Change => "change",
+            Action::Raise => "raise",
+            Action::Lower => "lower",
+            Action::Deadline => "deadline",
+            Action::Rename => "rename",
+            Action::Project => "project",
+            Action::Balance => "balance",
+            Action::Energy => "energy",
+            Action::Add => "add",
+            Action::QuickAdd => "quick_add",
+            Action::Stats => "stats",
+            Action::Forecast => "forecast",
+            Action::Today => "today",
+            Action::Focus => "focus",
+            Action::Settings => "settings",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Down => "Down",
+            Action::Up => "Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveUp => "Move Up",
+            Action::Mark => "Mark",
+            Action::Visual => "Select Range",
+            Action::Step => "Step",
+            Action::WorkOn => "Work on Now",
+            Action::Finish => "Finish",
+            Action::Pause => "Toggle Pause",
+            Action::Snooze => "Snooze",
+            Action::Remove => "Remove",
+            Action::Undo => "Undo",
+            Action::Change => "Change",
+            Action::Raise => "Raise",
+            Action::Lower => "Lower",
+            Action::Deadline => "Deadline",
+            Action::Rename => "Rename",
+            Action::Project => "Next Project",
+            Action::Balance => "Fair Share",
+            Action::Energy => "Energy",
+            Action::Add => "Add",
+            Action::QuickAdd => "Quick Add",
+            Action::Stats => "Stats",
+            Action::Forecast => "Forecast",
+            Action::Today => "Today",
+            Action::Focus => "Focus",
+            Action::Settings => "Settings",
+            Action::Help => "Help",
+            Action::Quit => "Quit",
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Down => &["j", "Down"],
+            Action::Up => &["k", "Up"],
+            Action::MoveDown => &["J"],
+            Action::MoveUp => &["K"],
+            Action::Mark => &["space"],
+            Action::Visual => &["v"],
+            Action::Step => &["s"],
+            Action::WorkOn => &["w", "Enter"],
+            Action::Finish => &["f"],
+            Action::Pause => &["p"],
+            Action::Snooze => &["z"],
+            Action::Remove => &["d"],
+            Action::Undo => &["u"],
+            Action::Change => &["e"],
+            Action::Raise => &["+"],
+            Action::Lower => &["-"],
+            Action::Deadline => &["D"],
+            Action::Rename => &["r"],
+            Action::Project => &["P"],
+            Action::Balance => &["B"],
+            Action::Energy => &["E"],
+            Action::Add => &["a"],
+            Action::QuickAdd => &["n"],
+            Action::Stats => &["S"],
+            Action::Forecast => &["F"],
+            Action::Today => &["t", "Tab"],
+            Action::Focus => &["o"],
+            Action::Settings => &[","],
+            Action::Help => &["?"],
+            Action::Quit => &["q", "Esc"],
+        }
+    }
+}
+
+/// One key, with the modifiers held down
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        // Shift is already part of the character typed, and of BackTab,
+        // which only some terminals report it with
+        if !matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+        Key {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    /// Parse a key like `j`, `S`, `Down`, `ctrl-d` or `alt-shift-Up`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        loop {
+            let lower = rest.to_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift-") {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            // A lone "-" is the minus key, not a modifier
+            if rest.len() == len {
+                break;
+            }
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+        let code = match rest.to_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lower => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => {
+                        let number = lower.strip_prefix('f')?.parse().ok()?;
+                        (1..=12).contains(&number).then_some(KeyCode::F(number))?
+                    }
+                }
+            }
+        };
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Some(Key { code, modifiers })
+    }
+
+    fn describe(&self) -> String {
+        let mut out = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            out += "C-";
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            out += "M-";
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            out += "S-";
+        }
+        out += &match self.code {
+            KeyCode::Char(' ') => String::from("Space"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::BackTab => String::from("S-Tab"),
+            code => format!("{:?}", code),
+        };
+        out
+    }
+}
+
+// A sequence of keys pressed one after the other, like `g g`
+fn parse_sequence(text: &str) -> Option<Vec<Key>> {
+    let keys: Option<Vec<Key>> = text.split_whitespace().map(Key::parse).collect();
+    keys.filter(|x| !x.is_empty())
+}
+
+fn describe_sequence(keys: &[Key]) -> String {
+    keys.iter()
+        .map(|x| x.describe())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What a key press did
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Feed {
+    Action(Action),
+    /// The start of a longer sequence
+    Pending,
+    Unbound,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    pending: Vec<Key>,
+    /// Things wrong with the configured keys, to show the user
+    pub problems: Vec<String>,
+}
+
+impl Keymap {
+    /// The default keys, with any set in the config in place of them.
+    /// Bindings that clash with one already made are left out.
+    pub fn new(config: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            problems: Vec::new(),
+        };
+        for name in config.keys() {
+            if !Action::ALL.iter().any(|x| x.name() == name) {
+                keymap.problems.push(format!("Unknown action \"{}\"", name));
+            }
+        }
+        // Configured keys go first, so they win over the defaults
+        for action in Action::ALL {
+            for text in config.get(action.name()).into_iter().flatten() {
+                match parse_sequence(text) {
+                    Some(sequence) => keymap.bind(sequence, action, true),
+                    None => keymap.problems.push(format!(
+                        "Can't understand \"{}\" for {}",
+                        text,
+                        action.name()
+                    )),
+                }
+            }
+        }
+        for action in Action::ALL {
+            if config.contains_key(action.name()) {
+                continue;
+            }
+            for text in action.default_keys() {
+                keymap.bind(parse_sequence(text).unwrap(), action, false);
+            }
+        }
+        keymap
+    }
+
+    // Bind the keys unless they clash with ones already bound, which
+    // is only a problem for keys the user asked for
+    fn bind(&mut self, sequence: Vec<Key>, action: Action, configured: bool) {
+        let clash = self
+            .bindings
+            .iter()
+            .find(|(keys, _)| keys.starts_with(&sequence) || sequence.starts_with(keys));
+        if let Some((keys, other)) = clash {
+            if configured && *other != action {
+                self.problems.push(format!(
+                    "{} for {} clashes with {} for {}",
+                    describe_sequence(&sequence),
+                    action.name(),
+                    describe_sequence(keys),
+                    other.name()
+                ));
+            }
+            return;
+        }
+        self.bindings.push((sequence, action));
+    }
+
+    /// Handle a key press, which may finish a sequence of them
+    pub fn feed(&mut self, event: &KeyEvent) -> Feed {
+        self.pending.push(Key::from_event(event));
+        let mut prefix = false;
+        for (keys, action) in &self.bindings {
+            if *keys == self.pending {
+                self.pending.clear();
+                return Feed::Action(*action);
+            }
+            prefix |= keys.starts_with(&self.pending);
+        }
+        if prefix {
+            return Feed::Pending;
+        }
+        // Start again from this key if it didn't carry on the sequence
+        if self.pending.len() > 1 {
+            self.pending.clear();
+            return self.feed(event);
+        }
+        self.pending.clear();
+        Feed::Unbound
+    }
+
+    /// Whether the key press is bound to `action` on its own
+    pub fn is(&self, event: &KeyEvent, action: Action) -> bool {
+        let key = Key::from_event(event);
+        self.bindings
+            .iter()
+            .any(|(keys, x)| *x == action && keys.len() == 1 && keys[0] == key)
+    }
+
+    /// The keys for `action`, like `j/Down`
+    pub fn keys(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .filter(|x| x.1 == action)
+            .map(|x| describe_sequence(&x.0))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Keys pressed so far of an unfinished sequence
+    pub fn pending(&self) -> String {
+        describe_sequence(&self.pending)
+    }
+}
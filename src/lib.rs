@@ -1 +1,11 @@
+pub mod agenda;
+pub mod calendar;
+pub mod capacity;
+pub mod config;
+pub mod keymap;
+pub mod notifications;
+pub mod parser;
+pub mod project;
 pub mod scheduler;
+pub mod stats;
+pub mod storage;
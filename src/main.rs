@@ -1,28 +1,200 @@
-use nasin::scheduler::{Task, Tasks};
+mod cli;
+mod focus;
+mod forecast;
+mod preferences;
+mod projects;
+mod settings;
+mod statistics;
+mod today;
 
-use std::cell::RefCell;
+use nasin::capacity::{self, AtRisk};
+use nasin::config::Config;
+use nasin::notifications::{Notification, NotificationKind, NotificationSettings, Notifier};
+use nasin::parser;
+use nasin::project::{Filter, Project};
+use nasin::scheduler::{
+    format_duration, format_estimate, local_deadline, Changes, Energy, Snooze, Task, Tasks,
+};
+use settings::Settings;
+
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
 use libadwaita::{prelude::*, SwitchRow};
 
-use gtk::glib;
-use gtk::{Button, Calendar, ListBox, Orientation, ScrolledWindow, SelectionMode};
-use libadwaita::{ActionRow, Application, ApplicationWindow, EntryRow, HeaderBar};
+use gtk::{gdk, gio, glib};
+use gtk::{
+    Button, Calendar, DragSource, DropDown, DropTarget, ListBox, ListBoxRow, MenuButton,
+    Orientation, ScrolledWindow, SelectionMode, SpinButton,
+};
+use libadwaita::{
+    ActionRow, Application, ApplicationWindow, Banner, EntryRow, HeaderBar, MessageDialog,
+    OverlaySplitView, ResponseAppearance, Toast, ToastOverlay, ViewStack, ViewSwitcher,
+};
+
+fn send_notification(app: &Application, notification: &Notification) {
+    let gio_notification = gio::Notification::new(&notification.title);
+    gio_notification.set_body(Some(&notification.body));
+    app.send_notification(Some(notification.kind.id()), &gio_notification);
+}
+
+// Create a checkbox action for each kind of notification, along with
+// the menu that shows them
+fn build_notification_menu(app: &Application, notifier: Rc<RefCell<Notifier>>) -> gio::Menu {
+    let menu = gio::Menu::new();
+    for kind in NotificationKind::ALL {
+        let enabled = notifier.borrow().settings.enabled(kind);
+        let name = format!("notify-{}", kind.id());
+        let action = gio::SimpleAction::new_stateful(&name, None, &enabled.to_variant());
+        action.connect_change_state(glib::clone!(@strong notifier => move |action, value| {
+            let Some(enabled) = value.and_then(|x| x.get::<bool>()) else {
+                return;
+            };
+            action.set_state(&enabled.to_variant());
+            let mut notifier = notifier.borrow_mut();
+            notifier.settings.set_enabled(kind, enabled);
+            notifier.settings.save();
+        }));
+        app.add_action(&action);
+        menu.append(Some(kind.label()), Some(&format!("app.{}", name)));
+    }
+    menu
+}
+
+fn is_flatpak() -> bool {
+    std::path::Path::new("/.flatpak-info").exists()
+}
+
+fn call_background_portal(app: &Application, method: &str, options: glib::VariantDict) {
+    let Some(connection) = app.dbus_connection() else {
+        return;
+    };
+    let parameters = match method {
+        "RequestBackground" => glib::Variant::tuple_from_iter(["".to_variant(), options.end()]),
+        _ => glib::Variant::tuple_from_iter([options.end()]),
+    };
+    connection.call(
+        Some("org.freedesktop.portal.Desktop"),
+        "/org/freedesktop/portal/desktop",
+        "org.freedesktop.portal.Background",
+        method,
+        Some(&parameters),
+        None,
+        gio::DBusCallFlags::NONE,
+        -1,
+        None::<&gio::Cancellable>,
+        |_| {},
+    );
+}
+
+// Under Flatpak, ask the background portal to let us keep running,
+// and to start us in the background when logging in
+fn request_background(app: &Application) {
+    if !is_flatpak() {
+        return;
+    }
+    let options = glib::VariantDict::new(None);
+    options.insert_value("reason", &"Remind you what to work on".to_variant());
+    options.insert_value("autostart", &true.to_variant());
+    options.insert_value(
+        "commandline",
+        &vec!["nasin", "--gapplication-service"].to_variant(),
+    );
+    call_background_portal(app, "RequestBackground", options);
+}
 
-fn build_subtitle(task: &Task) -> String {
+// Show the current task next to the app in the system's list of
+// background apps
+fn set_background_status(app: &Application, tasks: &Tasks) {
+    if !is_flatpak() {
+        return;
+    }
+    let message = match tasks.tasks.first().filter(|x| !x.paused) {
+        Some(task) => format!("Working on {}", task.name),
+        None => String::from("Nothing to do"),
+    };
+    let options = glib::VariantDict::new(None);
+    options.insert_value("message", &message.to_variant());
+    call_background_portal(app, "SetStatus", options);
+}
+
+fn build_subtitle(task: &Task, config: &Config) -> String {
     let mut out = format!("Priority: {}", task.priority);
+    if let Some(until) = task.snoozed_until {
+        out += &format!(" Snoozed until {}", config.format_deadline(&until));
+    }
     if let Some(date) = task.deadline {
-        out += &format!(" Deadline: {}", date.format("%Y-%m-%d"));
+        out += &format!(" Deadline: {}", config.format_deadline(&date));
+    }
+    if let Some(overdue) = task.overdue_by(Local::now()) {
+        out += &format!(" Overdue by {}", format_duration(overdue));
+    }
+    if let Some(recurrence) = task.recurrence {
+        out += &format!(" Repeats {}", recurrence.describe());
+    }
+    if let Some(estimate) = task.estimate {
+        out += &format!(" Estimate: {}", format_estimate(estimate as u64));
+    }
+    if let Some(energy) = task.energy {
+        out += &format!(" Energy: {}", energy.label());
+    }
+    for tag in &task.tags {
+        out += &format!(" #{}", tag);
     }
     out
 }
 
-fn create_row(task: &Task, list: &ListBox, tasks: Rc<RefCell<Tasks>>) -> ActionRow {
+fn create_row(
+    task: &Task,
+    config: &Config,
+    at_risk: Option<&AtRisk>,
+    project: Option<&Project>,
+) -> ActionRow {
+    let mut subtitle = build_subtitle(task, config);
+    if let Some(project) = project {
+        subtitle += &format!(" +{}", project.name);
+    }
+    if let Some(at_risk) = at_risk {
+        subtitle += &format!(" At risk: {}", at_risk.describe());
+    }
     let row = ActionRow::builder()
         .title(glib::markup_escape_text(&task.name))
-        .subtitle(build_subtitle(task))
+        .subtitle(glib::markup_escape_text(&subtitle))
         .build();
+    if !task.paused && task.is_overdue(Local::now()) {
+        row.add_css_class("error");
+    } else if at_risk.is_some() {
+        row.add_css_class("warning");
+    }
+    if let Some(project) = project {
+        row.add_prefix(&projects::color_dot(project));
+    }
+    // Drag a task onto another to put it in that one's place
+    if !task.paused {
+        let drag_source = DragSource::builder()
+            .actions(gdk::DragAction::MOVE)
+            .content(&gdk::ContentProvider::for_value(&task.id.to_value()))
+            .build();
+        drag_source.connect_drag_begin(|source, _| {
+            let icon = gtk::WidgetPaintable::new(Some(&source.widget()));
+            source.set_icon(Some(&icon), 0, 0);
+        });
+        row.add_controller(drag_source);
+        let drop_target = DropTarget::new(u64::static_type(), gdk::DragAction::MOVE);
+        drop_target.connect_drop(|target, value, _, _| {
+            let (Ok(row), Ok(id)) = (target.widget().downcast::<ListBoxRow>(), value.get::<u64>())
+            else {
+                return false;
+            };
+            row.activate_action(
+                "app.move-task",
+                Some(&(id, row.index() as u32).to_variant()),
+            )
+            .is_ok()
+        });
+        row.add_controller(drop_target);
+    }
     let pause_button = Button::builder()
         .icon_name(if task.paused {
             "media-playback-start"
@@ -30,56 +202,280 @@ fn create_row(task: &Task, list: &ListBox, tasks: Rc<RefCell<Tasks>>) -> ActionR
             "media-playback-pause"
         })
         .css_classes(vec!["flat"])
+        .action_name("app.toggle-pause")
+        .action_target(&task.id.to_variant())
+        .build();
+    let work_on_button = Button::builder()
+        .icon_name("media-skip-forward")
+        .tooltip_text("Work on This Now")
+        .css_classes(vec!["flat"])
+        .action_name("app.work-on")
+        .action_target(&task.id.to_variant())
         .build();
     let button = Button::builder()
         .icon_name("edit-delete")
         .css_classes(vec!["flat"])
+        .action_name("app.remove")
+        .action_target(&task.id.to_variant())
+        .build();
+    let snooze_menu = gio::Menu::new();
+    for (i, snooze) in Snooze::ALL.iter().enumerate() {
+        let item = gio::MenuItem::new(Some(snooze.label()), None);
+        item.set_action_and_target_value(
+            Some("app.snooze"),
+            Some(&(task.id, i as u32).to_variant()),
+        );
+        snooze_menu.append_item(&item);
+    }
+    let item = gio::MenuItem::new(Some("Pick a Date…"), None);
+    item.set_action_and_target_value(Some("app.snooze-until"), Some(&task.id.to_variant()));
+    snooze_menu.append_item(&item);
+    let snooze_button = MenuButton::builder()
+        .icon_name("alarm-symbolic")
+        .tooltip_text("Snooze")
+        .css_classes(vec!["flat"])
+        .menu_model(&snooze_menu)
+        .valign(gtk::Align::Center)
         .build();
+    row.add_suffix(&snooze_button);
+    row.add_suffix(&work_on_button);
     row.add_suffix(&pause_button);
     row.add_suffix(&button);
-    pause_button.connect_clicked(
-        glib::clone!(@strong task, @strong tasks, @weak list => move |_| {
-            tasks.borrow_mut().toggle_pause(&task);
-            build_list_from_tasks(&list, tasks.clone());
-        }),
-    );
-    button.connect_clicked(
-        glib::clone!(@strong task, @strong tasks, @weak list => move |_| {
-            tasks.borrow_mut().remove(task.clone());
-            build_list_from_tasks(&list, tasks.clone());
-        }),
-    );
     row
 }
 
-fn build_list_from_tasks(list: &ListBox, tasks: Rc<RefCell<Tasks>>) {
+// Ask before doing something to a task that the user might regret,
+// then go ahead if they agree
+fn confirm(
+    window: &ApplicationWindow,
+    heading: &str,
+    body: &str,
+    verb: &str,
+    then: impl Fn() + 'static,
+) {
+    let dialog = MessageDialog::new(Some(window), Some(heading), Some(body));
+    dialog.add_responses(&[("cancel", "Cancel"), ("confirm", verb)]);
+    dialog.set_response_appearance("confirm", ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+    dialog.connect_response(None, move |_, response| {
+        if response == "confirm" {
+            then();
+        }
+    });
+    dialog.present();
+}
+
+// Let the user know what happened, with a button to undo it
+fn show_undo_toast(toasts: &ToastOverlay, title: &str) {
+    let toast = Toast::builder()
+        .title(glib::markup_escape_text(title))
+        .button_label("Undo")
+        .action_name("app.undo")
+        .build();
+    toasts.add_toast(toast);
+}
+
+// Ask for a day and time to snooze a task until
+fn show_snooze_dialog(parent: &ApplicationWindow, then: impl Fn(DateTime<Local>) + 'static) {
+    let calendar = Calendar::new();
+    let hour = SpinButton::builder()
+        .adjustment(&gtk::Adjustment::new(9.0, 0.0, 23.0, 1.0, 1.0, 0.0))
+        .orientation(Orientation::Vertical)
+        .wrap(true)
+        .build();
+    let minute = SpinButton::builder()
+        .adjustment(&gtk::Adjustment::new(0.0, 0.0, 59.0, 1.0, 5.0, 0.0))
+        .orientation(Orientation::Vertical)
+        .wrap(true)
+        .build();
+    let time_picker = gtk::Box::builder()
+        .spacing(6)
+        .halign(gtk::Align::Center)
+        .build();
+    time_picker.append(&hour);
+    time_picker.append(&gtk::Label::new(Some(":")));
+    time_picker.append(&minute);
+    let snooze_button = Button::builder()
+        .label("Snooze")
+        .css_classes(vec!["suggested-action"])
+        .build();
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&calendar);
+    content.append(&time_picker);
+    content.append(&snooze_button);
+
+    let holder_box = gtk::Box::new(Orientation::Vertical, 0);
+    holder_box.append(&HeaderBar::new());
+    holder_box.append(&content);
+    let window = ApplicationWindow::builder()
+        .title("Snooze Until")
+        .modal(true)
+        .transient_for(parent)
+        .content(&holder_box)
+        .build();
+    snooze_button.connect_clicked(glib::clone!(@weak window => move |_| {
+        let picked = calendar.date();
+        let date = NaiveDate::from_ymd_opt(
+            picked.year(),
+            picked.month() as u32,
+            picked.day_of_month() as u32,
+        );
+        let time = NaiveTime::from_hms_opt(hour.value_as_int() as u32, minute.value_as_int() as u32, 0);
+        if let Some(until) = date.and_then(|x| local_deadline(x, time)) {
+            then(until);
+        }
+        window.close();
+    }));
+    window.present();
+}
+
+// Ids of the tasks whose rows are selected
+fn selected_ids(list: &ListBox, tasks: &Tasks) -> Vec<u64> {
+    list.selected_rows()
+        .iter()
+        .filter_map(|x| tasks.tasks.get(x.index() as usize))
+        .map(|x| x.id)
+        .collect()
+}
+
+// Warn about tasks that can't be finished before their deadlines
+fn update_banner(banner: &Banner, at_risk: &[AtRisk]) {
+    let title = match at_risk {
+        [] => String::new(),
+        [task] => format!("{} can't be finished before its deadline", task.name),
+        [task, rest @ ..] => format!(
+            "{} and {} more can't be finished before their deadlines",
+            task.name,
+            rest.len()
+        ),
+    };
+    banner.set_title(&glib::markup_escape_text(&title));
+    banner.set_revealed(!at_risk.is_empty());
+}
+
+fn build_list_from_tasks(
+    list: &ListBox,
+    banner: &Banner,
+    tasks: Rc<RefCell<Tasks>>,
+    settings: Rc<Settings>,
+) {
     list.remove_all();
-    for task in &tasks.borrow().tasks {
-        list.append(&create_row(task, list, tasks.clone()));
+    let tasks = tasks.borrow();
+    let at_risk = capacity::check(&tasks, Local::now());
+    for task in &tasks.tasks {
+        let risk = at_risk.iter().find(|x| x.task_id == task.id);
+        let project = task.project.and_then(|x| tasks.project(x));
+        list.append(&create_row(task, &settings.config(), risk, project));
     }
+    update_banner(banner, &at_risk);
 }
 
 fn build_ui(app: &Application) {
+    let settings = Settings::load();
     let tasks = Rc::new(RefCell::new(Tasks::load()));
+    let notifier = Rc::new(RefCell::new(Notifier::new(NotificationSettings::load())));
 
     let list = ListBox::builder()
         .margin_top(32)
         .margin_end(32)
         .margin_bottom(32)
         .margin_start(32)
-        .selection_mode(SelectionMode::None)
+        .selection_mode(SelectionMode::Multiple)
         .css_classes(vec!["boxed-list"])
         .build();
-    build_list_from_tasks(&list, tasks.clone());
+    // Only the tasks in the project picked in the sidebar are shown
+    let filter = Rc::new(Cell::new(Filter::All));
+    list.set_filter_func(glib::clone!(@strong tasks, @strong filter => move |row| {
+        tasks
+            .borrow()
+            .tasks
+            .get(row.index() as usize)
+            .is_some_and(|x| filter.get().matches(x))
+    }));
+    let sidebar = ListBox::builder()
+        .css_classes(vec!["navigation-sidebar"])
+        .build();
+    projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+    sidebar.connect_row_selected(
+        glib::clone!(@strong tasks, @strong filter, @weak list => move |_, row| {
+            if let Some(row) = row {
+                filter.set(projects::filter_at(&tasks.borrow(), row.index() as usize));
+                list.invalidate_filter();
+            }
+        }),
+    );
+    let banner = Banner::builder()
+        .button_label("Forecast")
+        .action_name("app.forecast")
+        .build();
+    build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
 
     let add_button = Button::builder().icon_name("list-add").build();
     let step_button = Button::builder().icon_name("edit-redo").build();
     let step_and_finish_button = Button::builder().icon_name("emblem-ok").build();
+    let menu = gio::Menu::new();
+    menu.append(Some("Show Current Task"), Some("app.show-current"));
+    menu.append(Some("Focus Mode"), Some("app.focus"));
+    menu.append(Some("Run in Background"), Some("app.background"));
+    menu.append(Some("Statistics"), Some("app.statistics"));
+    menu.append(Some("Forecast"), Some("app.forecast"));
+    menu.append(Some("Preferences"), Some("app.preferences"));
+    menu.append_section(
+        Some("Notifications"),
+        &build_notification_menu(app, notifier.clone()),
+    );
+    let menu_button = MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .menu_model(&menu)
+        .build();
 
     let header_bar = HeaderBar::builder().build();
     header_bar.pack_start(&add_button);
     header_bar.pack_start(&step_button);
     header_bar.pack_start(&step_and_finish_button);
+    header_bar.pack_end(&menu_button);
+
+    // How much energy there is right now, to favour tasks that match
+    let energy_choices = [
+        None,
+        Some(Energy::Low),
+        Some(Energy::Medium),
+        Some(Energy::High),
+    ];
+    let energy_dropdown =
+        DropDown::from_strings(&["Any Energy", "Low Energy", "Medium Energy", "High Energy"]);
+    energy_dropdown.set_tooltip_text(Some("How much energy you have right now"));
+    energy_dropdown.set_selected(
+        energy_choices
+            .iter()
+            .position(|x| *x == tasks.borrow().energy)
+            .unwrap_or(0) as u32,
+    );
+    energy_dropdown.connect_selected_notify(
+        glib::clone!(@strong tasks, @strong settings, @weak list, @weak banner => move |dropdown| {
+            let energy = energy_choices[dropdown.selected() as usize % energy_choices.len()];
+            if energy != tasks.borrow().energy {
+                tasks.borrow_mut().set_energy(energy);
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            }
+        }),
+    );
+    header_bar.pack_end(&energy_dropdown);
+
+    let quick_add_entry = gtk::Entry::builder()
+        .placeholder_text("Call dentist tomorrow 3pm !2 #health")
+        .primary_icon_name("list-add-symbolic")
+        .hexpand(true)
+        .build();
+    header_bar.set_title_widget(Some(&quick_add_entry));
 
     let viewport = ScrolledWindow::builder()
         .child(&list)
@@ -87,14 +483,112 @@ fn build_ui(app: &Application) {
         .min_content_height(400)
         .build();
 
+    let today_page = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(24)
+        .margin_top(32)
+        .margin_end(32)
+        .margin_bottom(32)
+        .margin_start(32)
+        .build();
+    let stack = ViewStack::new();
+    stack.add_titled_with_icon(&viewport, Some("tasks"), "Tasks", "view-list-symbolic");
+    stack.add_titled_with_icon(
+        &ScrolledWindow::builder()
+            .child(&today_page)
+            .vexpand(true)
+            .build(),
+        Some("today"),
+        "Today",
+        "x-office-calendar-symbolic",
+    );
+    header_bar.pack_end(&ViewSwitcher::builder().stack(&stack).build());
+    // The plan is only worked out while it's being looked at
+    stack.connect_visible_child_name_notify(
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @weak today_page => move |stack| {
+            if stack.visible_child_name().as_deref() == Some("today") {
+                let quantum_minutes = notifier.borrow().settings.quantum_minutes;
+                today::build_today(&today_page, &tasks.borrow(), &settings.config(), quantum_minutes);
+            }
+        }),
+    );
+
+    let toasts = ToastOverlay::new();
+    toasts.set_child(Some(&stack));
+
+    // Shown while rows are selected, to change them all at once
+    let selection_label = gtk::Label::new(None);
+    let bulk_pause_button = Button::builder()
+        .icon_name("media-playback-pause")
+        .tooltip_text("Pause or Resume")
+        .build();
+    let bulk_remove_button = Button::builder()
+        .icon_name("edit-delete")
+        .tooltip_text("Remove")
+        .css_classes(vec!["destructive-action"])
+        .build();
+    let bulk_entry = gtk::Entry::builder()
+        .placeholder_text("!2 #work friday 5pm")
+        .tooltip_text("Set the priority, tags or deadline")
+        .primary_icon_name("document-edit-symbolic")
+        .hexpand(true)
+        .build();
+    let action_bar = gtk::ActionBar::builder().revealed(false).build();
+    action_bar.pack_start(&selection_label);
+    action_bar.set_center_widget(Some(&bulk_entry));
+    action_bar.pack_end(&bulk_remove_button);
+    action_bar.pack_end(&bulk_pause_button);
+    list.connect_selected_rows_changed(
+        glib::clone!(@weak action_bar, @weak selection_label => move |list| {
+            let selected = list.selected_rows().len();
+            action_bar.set_revealed(selected > 0);
+            selection_label.set_label(&format!("{} selected", selected));
+        }),
+    );
+
     let content = gtk::Box::new(Orientation::Vertical, 0);
     content.append(&header_bar);
-    content.append(&viewport);
+    content.append(&banner);
+    content.append(&toasts);
+    content.append(&action_bar);
+
+    let new_project_button = Button::builder()
+        .icon_name("list-add-symbolic")
+        .tooltip_text("New Project")
+        .action_name("app.new-project")
+        .build();
+    let sidebar_header = HeaderBar::builder()
+        .title_widget(&libadwaita::WindowTitle::new("Projects", ""))
+        .build();
+    sidebar_header.pack_start(&new_project_button);
+    let sidebar_box = gtk::Box::new(Orientation::Vertical, 0);
+    sidebar_box.append(&sidebar_header);
+    sidebar_box.append(
+        &ScrolledWindow::builder()
+            .child(&sidebar)
+            .vexpand(true)
+            .build(),
+    );
+    let split_view = OverlaySplitView::builder()
+        .sidebar(&sidebar_box)
+        .content(&content)
+        .build();
+    let sidebar_button = gtk::ToggleButton::builder()
+        .icon_name("sidebar-show-symbolic")
+        .tooltip_text("Projects")
+        .build();
+    sidebar_button
+        .bind_property("active", &split_view, "show-sidebar")
+        .sync_create()
+        .bidirectional()
+        .build();
+    header_bar.pack_start(&sidebar_button);
 
     let window = ApplicationWindow::builder()
         .application(app)
         .title("Nasin")
-        .content(&content)
+        .content(&split_view)
+        .hide_on_close(true)
         .build();
 
     let add_task_title_row = EntryRow::builder().title("Name").build();
@@ -111,6 +605,27 @@ fn build_ui(app: &Application) {
         .build();
     let add_task_calendar_toggle = SwitchRow::builder().title("Date").build();
     let add_task_calendar = Calendar::builder().sensitive(false).build();
+    let add_task_time_toggle = SwitchRow::builder().title("Time").sensitive(false).build();
+    let add_task_hour = SpinButton::builder()
+        .adjustment(&gtk::Adjustment::new(12.0, 0.0, 23.0, 1.0, 1.0, 0.0))
+        .orientation(Orientation::Vertical)
+        .wrap(true)
+        .build();
+    let add_task_minute = SpinButton::builder()
+        .adjustment(&gtk::Adjustment::new(0.0, 0.0, 59.0, 1.0, 5.0, 0.0))
+        .orientation(Orientation::Vertical)
+        .wrap(true)
+        .build();
+    let add_task_time_picker = gtk::Box::builder()
+        .spacing(6)
+        .halign(gtk::Align::Center)
+        .margin_top(6)
+        .margin_bottom(6)
+        .sensitive(false)
+        .build();
+    add_task_time_picker.append(&add_task_hour);
+    add_task_time_picker.append(&gtk::Label::new(Some(":")));
+    add_task_time_picker.append(&add_task_minute);
     let add_task_content = gtk::ListBox::builder()
         .margin_top(12)
         .margin_bottom(12)
@@ -122,6 +637,8 @@ fn build_ui(app: &Application) {
     add_task_content.append(&add_task_priority_row);
     add_task_content.append(&add_task_calendar_toggle);
     add_task_content.append(&add_task_calendar);
+    add_task_content.append(&add_task_time_toggle);
+    add_task_content.append(&add_task_time_picker);
 
     let holder_box = gtk::Box::new(Orientation::Vertical, 0);
     holder_box.append(&HeaderBar::new());
@@ -140,52 +657,584 @@ fn build_ui(app: &Application) {
         add_task_window.present();
     }));
 
-    step_button.connect_clicked(glib::clone!(@strong tasks, @weak list => move |_| {
-        tasks.borrow_mut().step();
-        build_list_from_tasks(&list, tasks.clone());
+    step_button.set_action_name(Some("app.step"));
+    step_and_finish_button.set_action_name(Some("app.finish"));
+
+    // Pick up changes made through the CLI or the TUI since, which our
+    // next save would otherwise write over
+    let reload = Rc::new(
+        glib::clone!(@strong tasks, @strong settings, @strong filter, @weak app, @weak list, @weak banner, @weak sidebar => move || {
+            if !tasks.borrow_mut().reload() {
+                return;
+            }
+            projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+    window.connect_is_active_notify(glib::clone!(@strong reload => move |window| {
+        if window.is_active() {
+            reload();
+        }
+    }));
+
+    let step_action = gio::SimpleAction::new("step", None);
+    step_action.connect_activate(
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @strong reload, @weak app, @weak list, @weak banner => move |_, _| {
+            reload();
+            let promoted = tasks.borrow_mut().step();
+            notifier.borrow_mut().start_quantum(Local::now());
+            if let Some(notification) = promoted.and_then(|x| notifier.borrow().promoted(&x)) {
+                send_notification(&app, &notification);
+            }
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+    app.add_action(&step_action);
+
+    // Finish the task that was asked about, even if the head of the
+    // queue has changed since
+    let finish = Rc::new(
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @strong reload, @weak app, @weak list, @weak banner, @weak toasts => move |task: Task| {
+            reload();
+            if tasks.borrow().find(task.id).is_none_or(|x| x.paused) {
+                return;
+            }
+            let promoted = tasks.borrow_mut().finish(task.id);
+            notifier.borrow_mut().start_quantum(Local::now());
+            if let Some(notification) = promoted.and_then(|x| notifier.borrow().promoted(&x)) {
+                send_notification(&app, &notification);
+            }
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            show_undo_toast(&toasts, &format!("Finished {}", task.name));
+        }),
+    );
+    let finish_action = gio::SimpleAction::new("finish", None);
+    finish_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @strong reload, @weak window => move |_, _| {
+            reload();
+            let Some(task) = tasks.borrow().tasks.first().filter(|x| !x.paused).cloned() else {
+                return;
+            };
+            // Finishing from a notification doesn't need the window
+            if !settings.config().confirm.finish || !window.is_visible() {
+                finish(task);
+                return;
+            }
+            let finish = finish.clone();
+            confirm(
+                &window,
+                "Finish Task?",
+                &format!("{} will be marked as done", task.name),
+                "Finish",
+                move || finish(task.clone()),
+            );
+        }),
+    );
+    app.add_action(&finish_action);
+
+    // Remove the task that was asked about, by id, since it may have
+    // aged or changed priority while the dialog was open
+    let remove = Rc::new(
+        glib::clone!(@strong tasks, @strong settings, @strong reload, @weak list, @weak banner, @weak toasts => move |task: Task| {
+            reload();
+            if tasks.borrow().find(task.id).is_none() {
+                return;
+            }
+            tasks.borrow_mut().remove_all(&[task.id]);
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            show_undo_toast(&toasts, &format!("Removed {}", task.name));
+        }),
+    );
+    let remove_action = gio::SimpleAction::new("remove", Some(glib::VariantTy::UINT64));
+    remove_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @weak window => move |_, parameter| {
+            let Some(id) = parameter.and_then(|x| x.get::<u64>()) else {
+                return;
+            };
+            let Some(task) = tasks.borrow().find(id).cloned() else {
+                return;
+            };
+            if !settings.config().confirm.remove || !window.is_visible() {
+                remove(task);
+                return;
+            }
+            let remove = remove.clone();
+            confirm(
+                &window,
+                "Remove Task?",
+                &format!("{} will be removed from the list", task.name),
+                "Remove",
+                move || remove(task.clone()),
+            );
+        }),
+    );
+    app.add_action(&remove_action);
+
+    // Change every selected task, asking first if there are several
+    let change_selected = Rc::new(
+        glib::clone!(@strong tasks, @strong settings, @weak window, @weak list, @weak banner, @weak toasts => move |changes: Changes| {
+            let ids = selected_ids(&list, &tasks.borrow());
+            let change = glib::clone!(@strong tasks, @strong settings, @weak list, @weak banner, @weak toasts => move || {
+                tasks.borrow_mut().change(&ids, &changes);
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+                show_undo_toast(&toasts, &format!("Changed {} tasks", ids.len()));
+            });
+            let count = list.selected_rows().len();
+            if count > 1 && settings.config().confirm.bulk {
+                confirm(
+                    &window,
+                    "Change Tasks?",
+                    &format!("{} tasks will be changed", count),
+                    "Change",
+                    change,
+                );
+            } else if count > 0 {
+                change();
+            }
+        }),
+    );
+    bulk_pause_button.connect_clicked(
+        glib::clone!(@strong tasks, @strong change_selected, @weak list => move |_| {
+            let ids = selected_ids(&list, &tasks.borrow());
+            let paused = ids
+                .iter()
+                .filter_map(|x| tasks.borrow().find(*x).map(|x| x.paused))
+                .any(|x| !x);
+            change_selected(Changes {
+                paused: Some(paused),
+                ..Changes::default()
+            });
+        }),
+    );
+    bulk_entry.connect_activate(
+        glib::clone!(@strong tasks, @strong filter, @strong change_selected, @weak sidebar => move |entry| {
+            let parsed = parser::parse(&entry.text(), Local::now());
+            let project = parsed.project_in(&mut tasks.borrow_mut());
+            if parsed.project.is_some() {
+                projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+            }
+            let mut changes = parsed.into_changes();
+            changes.project = project.map(Some);
+            if changes != Changes::default() {
+                change_selected(changes);
+                entry.set_text("");
+            }
+        }),
+    );
+    bulk_remove_button.connect_clicked(
+        glib::clone!(@strong tasks, @strong settings, @weak window, @weak list, @weak banner, @weak toasts => move |_| {
+            let ids = selected_ids(&list, &tasks.borrow());
+            let remove = glib::clone!(@strong tasks, @strong settings, @weak list, @weak banner, @weak toasts => move || {
+                tasks.borrow_mut().remove_all(&ids);
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+                show_undo_toast(&toasts, &format!("Removed {} tasks", ids.len()));
+            });
+            let count = list.selected_rows().len();
+            let ask = if count > 1 {
+                settings.config().confirm.bulk
+            } else {
+                settings.config().confirm.remove
+            };
+            if ask {
+                confirm(
+                    &window,
+                    "Remove Tasks?",
+                    &format!("{} tasks will be removed from the list", count),
+                    "Remove",
+                    remove,
+                );
+            } else {
+                remove();
+            }
+        }),
+    );
+
+    let undo_action = gio::SimpleAction::new("undo", None);
+    undo_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @weak app, @weak list, @weak banner => move |_, _| {
+            if tasks.borrow_mut().undo() {
+                set_background_status(&app, &tasks.borrow());
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            }
+        }),
+    );
+    app.add_action(&undo_action);
+    app.set_accels_for_action("app.undo", &["<Ctrl>z"]);
+
+    let snooze = Rc::new(
+        glib::clone!(@strong tasks, @strong settings, @weak app, @weak list, @weak banner => move |id: u64, until: DateTime<Local>| {
+            tasks.borrow_mut().snooze(&[id], until);
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+    let snooze_action =
+        gio::SimpleAction::new("snooze", Some(&<(u64, u32)>::static_variant_type()));
+    snooze_action.connect_activate(glib::clone!(@strong snooze => move |_, parameter| {
+        let Some((id, i)) = parameter.and_then(|x| x.get::<(u64, u32)>()) else {
+            return;
+        };
+        if let Some(preset) = Snooze::ALL.get(i as usize) {
+            snooze(id, preset.until(Local::now()));
+        }
     }));
+    app.add_action(&snooze_action);
+    let snooze_until_action = gio::SimpleAction::new("snooze-until", Some(glib::VariantTy::UINT64));
+    snooze_until_action.connect_activate(
+        glib::clone!(@strong snooze, @weak window => move |_, parameter| {
+            let Some(id) = parameter.and_then(|x| x.get::<u64>()) else {
+                return;
+            };
+            let snooze = snooze.clone();
+            show_snooze_dialog(&window, move |until| snooze(id, until));
+        }),
+    );
+    app.add_action(&snooze_until_action);
 
-    step_and_finish_button.connect_clicked(glib::clone!(@strong tasks, @weak list => move |_| {
-        tasks.borrow_mut().step_and_finish();
-        build_list_from_tasks(&list, tasks.clone())
+    let toggle_pause_action = gio::SimpleAction::new("toggle-pause", Some(glib::VariantTy::UINT64));
+    toggle_pause_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @weak app, @weak list, @weak banner => move |_, parameter| {
+            let Some(id) = parameter.and_then(|x| x.get::<u64>()) else {
+                return;
+            };
+            let Some(task) = tasks.borrow().find(id).cloned() else {
+                return;
+            };
+            tasks.borrow_mut().toggle_pause(&task);
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+    app.add_action(&toggle_pause_action);
+
+    let work_on_action = gio::SimpleAction::new("work-on", Some(glib::VariantTy::UINT64));
+    work_on_action.connect_activate(
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @weak app, @weak list, @weak banner => move |_, parameter| {
+            let Some(id) = parameter.and_then(|x| x.get::<u64>()) else {
+                return;
+            };
+            let promoted = tasks.borrow_mut().work_on(id);
+            notifier.borrow_mut().start_quantum(Local::now());
+            if let Some(notification) = promoted.and_then(|x| notifier.borrow().promoted(&x)) {
+                send_notification(&app, &notification);
+            }
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+    app.add_action(&work_on_action);
+
+    // Put a task somewhere else in the queue, from dragging it
+    let move_task_action =
+        gio::SimpleAction::new("move-task", Some(glib::VariantTy::new("(tu)").unwrap()));
+    move_task_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @weak app, @weak list, @weak banner, @weak toasts => move |_, parameter| {
+            let Some((id, index)) = parameter.and_then(|x| x.get::<(u64, u32)>()) else {
+                return;
+            };
+            if !tasks.borrow_mut().move_task(id, index as usize) {
+                return;
+            }
+            set_background_status(&app, &tasks.borrow());
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            show_undo_toast(&toasts, "Moved task");
+        }),
+    );
+    app.add_action(&move_task_action);
+
+    // Tell the user what to work on, with buttons to move on from it
+    let show_current_action = gio::SimpleAction::new("show-current", None);
+    show_current_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @strong reload, @weak app => move |_, _| {
+            reload();
+            let notification = match tasks.borrow().tasks.first().filter(|x| !x.paused) {
+                Some(task) => {
+                    let notification = gio::Notification::new(&format!("Work on {}", task.name));
+                    notification.set_body(Some(&build_subtitle(task, &settings.config())));
+                    notification.add_button("Step", "app.step");
+                    notification.add_button("Finish", "app.finish");
+                    notification
+                }
+                None => gio::Notification::new("Nothing to do"),
+            };
+            notification.set_default_action("app.show-window");
+            app.send_notification(Some("current-task"), &notification);
+        }),
+    );
+    app.add_action(&show_current_action);
+
+    // Only one focus window at a time, which the shortcut opens and
+    // closes again
+    let focus_window: Rc<RefCell<Option<ApplicationWindow>>> = Rc::new(RefCell::new(None));
+    let focus_action = gio::SimpleAction::new("focus", None);
+    focus_action.connect_activate(
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @weak app => move |_, _| {
+            let open = focus_window.borrow_mut().take();
+            if let Some(window) = open {
+                window.close();
+                return;
+            }
+            let window = focus::show_focus(&app, tasks.clone(), notifier.clone(), settings.clone());
+            window.connect_close_request(
+                glib::clone!(@strong focus_window => @default-return glib::Propagation::Proceed, move |_| {
+                    focus_window.borrow_mut().take();
+                    glib::Propagation::Proceed
+                }),
+            );
+            *focus_window.borrow_mut() = Some(window);
+        }),
+    );
+    app.add_action(&focus_action);
+    app.set_accels_for_action("app.focus", &["<Ctrl><Shift>f"]);
+
+    let statistics_action = gio::SimpleAction::new("statistics", None);
+    statistics_action.connect_activate(glib::clone!(@strong tasks, @weak window => move |_, _| {
+        statistics::show_statistics(&window, &tasks.borrow());
+    }));
+    app.add_action(&statistics_action);
+
+    let forecast_action = gio::SimpleAction::new("forecast", None);
+    forecast_action.connect_activate(
+        glib::clone!(@strong tasks, @strong notifier, @weak window => move |_, _| {
+            let quantum_minutes = notifier.borrow().settings.quantum_minutes;
+            forecast::show_forecast(&window, &tasks.borrow(), quantum_minutes);
+        }),
+    );
+    app.add_action(&forecast_action);
+
+    let new_project_action = gio::SimpleAction::new("new-project", None);
+    new_project_action.connect_activate(
+        glib::clone!(@strong tasks, @strong filter, @weak window, @weak sidebar => move |_, _| {
+            projects::show_project_dialog(
+                &window,
+                Project::new(String::new()),
+                glib::clone!(@strong tasks, @strong filter, @weak sidebar => move |project| {
+                    tasks.borrow_mut().add_project(project);
+                    projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+                }),
+                || {},
+            );
+        }),
+    );
+    app.add_action(&new_project_action);
+
+    let edit_project_action = gio::SimpleAction::new("edit-project", Some(glib::VariantTy::UINT64));
+    edit_project_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @strong filter, @weak window, @weak list, @weak banner, @weak sidebar => move |_, parameter| {
+            let Some(project) = parameter
+                .and_then(|x| x.get::<u64>())
+                .and_then(|x| tasks.borrow().project(x).cloned())
+            else {
+                return;
+            };
+            let id = project.id;
+            projects::show_project_dialog(
+                &window,
+                project,
+                // Archiving pauses the project's tasks
+                glib::clone!(@strong tasks, @strong settings, @strong filter, @weak list, @weak banner, @weak sidebar => move |project| {
+                    tasks.borrow_mut().update_project(project);
+                    projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+                    build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+                }),
+                glib::clone!(@strong tasks, @strong settings, @strong filter, @weak list, @weak banner, @weak sidebar => move || {
+                    tasks.borrow_mut().remove_project(id);
+                    if filter.get() == Filter::Project(id) {
+                        filter.set(Filter::All);
+                    }
+                    projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+                    build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+                }),
+            );
+        }),
+    );
+    app.add_action(&edit_project_action);
+
+    let preferences_action = gio::SimpleAction::new("preferences", None);
+    preferences_action.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @weak window, @weak list, @weak banner => move |_, _| {
+            let preferences = preferences::show_preferences(&window, tasks.clone(), settings.clone());
+            // Priorities change with how deadlines are worked out
+            preferences.connect_close_request(
+                glib::clone!(@strong tasks, @strong settings, @weak list, @weak banner => @default-return glib::Propagation::Proceed, move |_| {
+                    build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+                    glib::Propagation::Proceed
+                }),
+            );
+        }),
+    );
+    app.add_action(&preferences_action);
+    app.set_accels_for_action("app.preferences", &["<Ctrl>comma"]);
+
+    let show_window_action = gio::SimpleAction::new("show-window", None);
+    show_window_action.connect_activate(glib::clone!(@weak window => move |_, _| {
+        window.present();
     }));
+    app.add_action(&show_window_action);
+
+    // Keep the app alive without a window while this is on
+    let hold_guard: RefCell<Option<gio::ApplicationHoldGuard>> = RefCell::new(None);
+    let background_action =
+        gio::SimpleAction::new_stateful("background", None, &false.to_variant());
+    background_action.connect_change_state(
+        glib::clone!(@strong tasks, @weak app => move |action, value| {
+            let Some(enabled) = value.and_then(|x| x.get::<bool>()) else {
+                return;
+            };
+            action.set_state(&enabled.to_variant());
+            if enabled {
+                hold_guard.replace(Some(app.hold()));
+                request_background(&app);
+                set_background_status(&app, &tasks.borrow());
+            } else {
+                hold_guard.take();
+            }
+        }),
+    );
+    app.add_action(&background_action);
+    // Started with --gapplication-service, so there's no window to keep us running
+    if app.flags().contains(gio::ApplicationFlags::IS_SERVICE) {
+        background_action.change_state(&true.to_variant());
+    }
+
+    window.connect_close_request(
+        glib::clone!(@weak app, @weak background_action => @default-return glib::Propagation::Proceed, move |_| {
+            let background = background_action
+                .state()
+                .and_then(|x| x.get::<bool>())
+                .unwrap_or(false);
+            if !background {
+                app.quit();
+            }
+            glib::Propagation::Proceed
+        }),
+    );
+
+    // Dates are shown differently if the format changes
+    settings.connect_changed(
+        glib::clone!(@strong tasks, @weak settings, @weak list, @weak banner => move |_| {
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+        }),
+    );
+
+    quick_add_entry.connect_activate(
+        glib::clone!(@strong tasks, @strong settings, @strong filter, @weak list, @weak banner, @weak sidebar => move |entry| {
+            let parsed = parser::parse(&entry.text(), Local::now());
+            if parsed.name.is_empty() {
+                return;
+            }
+            let project = parsed.project_in(&mut tasks.borrow_mut());
+            if parsed.project.is_some() {
+                projects::build_sidebar(&sidebar, &tasks.borrow(), filter.get());
+            }
+            let mut task = parsed.into_task_or(settings.config().default_priority);
+            task.project = project;
+            tasks.borrow_mut().add(task);
+            build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            entry.set_text("");
+        }),
+    );
+
+    // Check for deadlines and finished work sessions every so often
+    glib::timeout_add_seconds_local(
+        30,
+        glib::clone!(@strong tasks, @strong notifier, @strong settings, @strong reload, @weak app, @weak list, @weak banner, @weak stack, @weak today_page => @default-return glib::ControlFlow::Break, move || {
+            reload();
+            let woken = tasks.borrow_mut().wake(Local::now());
+            let modified = tasks.borrow_mut().update_deadlines(Local::now());
+            if modified || !woken.is_empty() {
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
+            }
+            if stack.visible_child_name().as_deref() == Some("today") {
+                let quantum_minutes = notifier.borrow().settings.quantum_minutes;
+                today::build_today(&today_page, &tasks.borrow(), &settings.config(), quantum_minutes);
+            }
+            for notification in notifier.borrow_mut().poll(&tasks.borrow(), Local::now()) {
+                send_notification(&app, &notification);
+            }
+            glib::ControlFlow::Continue
+        }),
+    );
 
     add_task_create_button.connect_clicked(
-        glib::clone!(@weak add_task_calendar_toggle, @weak add_task_calendar => move |_| {
+        glib::clone!(@weak add_task_calendar_toggle, @weak add_task_calendar, @weak add_task_time_toggle => move |_| {
             let name: String = add_task_title_row.text().to_string();
-            let priority: u8 = add_task_priority_row.text().parse().unwrap_or(1);
+            let priority: u8 = add_task_priority_row
+                .text()
+                .parse()
+                .unwrap_or(settings.config().default_priority);
             let date: Option<DateTime<Local>> = if add_task_calendar_toggle.is_active() {
-                let timestamp = add_task_calendar.date().to_unix();
-                Some(DateTime::<Utc>::from_timestamp(timestamp, 0).unwrap().with_timezone(&Local))
+                // Go by the day picked rather than its timestamp, which is
+                // midnight UTC and can land on another day locally
+                let picked = add_task_calendar.date();
+                let time = if add_task_time_toggle.is_active() {
+                    NaiveTime::from_hms_opt(
+                        add_task_hour.value_as_int() as u32,
+                        add_task_minute.value_as_int() as u32,
+                        0,
+                    )
+                } else {
+                    None
+                };
+                NaiveDate::from_ymd_opt(picked.year(), picked.month() as u32, picked.day_of_month() as u32)
+                    .and_then(|x| local_deadline(x, time))
             } else {
                 None
             };
             if priority >= 1 {
                 tasks.borrow_mut().add(Task::new(name, priority, date));
-                build_list_from_tasks(&list, tasks.clone());
+                build_list_from_tasks(&list, &banner, tasks.clone(), settings.clone());
                 add_task_window.close();
             }
             // Reset the text of the rows
             add_task_title_row.set_text("");
             add_task_priority_row.set_text("");
             add_task_calendar_toggle.set_active(false);
+            add_task_time_toggle.set_active(false);
         }),
     );
 
     add_task_calendar_toggle.connect_active_notify(
-        glib::clone!(@strong add_task_calendar => move |toggle| {
+        glib::clone!(@strong add_task_calendar, @strong add_task_time_toggle => move |toggle| {
             add_task_calendar.set_sensitive(toggle.is_active());
+            add_task_time_toggle.set_sensitive(toggle.is_active());
+        }),
+    );
+
+    add_task_time_toggle.connect_sensitive_notify(
+        glib::clone!(@strong add_task_time_picker => move |toggle| {
+            add_task_time_picker.set_sensitive(toggle.is_sensitive() && toggle.is_active());
+        }),
+    );
+    add_task_time_toggle.connect_active_notify(
+        glib::clone!(@strong add_task_time_picker => move |toggle| {
+            add_task_time_picker.set_sensitive(toggle.is_sensitive() && toggle.is_active());
         }),
     );
-    window.present();
 }
 
-fn main() {
+fn main() -> glib::ExitCode {
+    // Subcommands like `nasin add` don't need the GUI at all
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        return code;
+    }
+
     let application = Application::builder()
         .application_id("me.jonot.Nasin")
         .build();
 
-    application.connect_activate(build_ui);
+    // Everything is set up on startup, so the app can run in the
+    // background before the window is ever shown
+    application.connect_startup(build_ui);
+    application.connect_activate(|app| {
+        if let Some(window) = app.windows().first() {
+            window.present();
+        }
+    });
 
-    application.run();
+    application.run()
 }
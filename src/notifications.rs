@@ -0,0 +1,231 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{format_deadline, Task, Tasks, DEFAULT_QUANTUM_MINUTES};
+use crate::storage::get_data_dir;
+
+fn get_settings_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("notifications");
+    path.set_extension("json");
+    path
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum NotificationKind {
+    DeadlineApproaching,
+    DeadlinePassed,
+    QuantumEnded,
+    TaskPromoted,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 4] = [
+        NotificationKind::DeadlineApproaching,
+        NotificationKind::DeadlinePassed,
+        NotificationKind::QuantumEnded,
+        NotificationKind::TaskPromoted,
+    ];
+
+    /// Short identifier, used for action names and notification ids
+    pub fn id(&self) -> &'static str {
+        match self {
+            NotificationKind::DeadlineApproaching => "deadline-approaching",
+            NotificationKind::DeadlinePassed => "deadline-passed",
+            NotificationKind::QuantumEnded => "quantum-ended",
+            NotificationKind::TaskPromoted => "task-promoted",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationKind::DeadlineApproaching => "Deadline Approaching",
+            NotificationKind::DeadlinePassed => "Deadline Passed",
+            NotificationKind::QuantumEnded => "Work Session Over",
+            NotificationKind::TaskPromoted => "Task Promoted",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub deadline_approaching: bool,
+    pub deadline_passed: bool,
+    pub quantum_ended: bool,
+    pub task_promoted: bool,
+    /// How many hours before a deadline to warn about it
+    pub approaching_hours: u32,
+    /// How long to work on a task before being told to step
+    pub quantum_minutes: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            deadline_approaching: true,
+            deadline_passed: true,
+            quantum_ended: true,
+            task_promoted: true,
+            approaching_hours: 24,
+            quantum_minutes: DEFAULT_QUANTUM_MINUTES,
+        }
+    }
+}
+
+impl NotificationSettings {
+    pub fn load() -> Self {
+        let path = get_settings_path();
+        File::open(path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(file).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let file = File::create(get_settings_path()).unwrap();
+        serde_json::to_writer(file, self).unwrap();
+    }
+
+    pub fn enabled(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::DeadlineApproaching => self.deadline_approaching,
+            NotificationKind::DeadlinePassed => self.deadline_passed,
+            NotificationKind::QuantumEnded => self.quantum_ended,
+            NotificationKind::TaskPromoted => self.task_promoted,
+        }
+    }
+
+    pub fn set_enabled(&mut self, kind: NotificationKind, enabled: bool) {
+        let flag = match kind {
+            NotificationKind::DeadlineApproaching => &mut self.deadline_approaching,
+            NotificationKind::DeadlinePassed => &mut self.deadline_passed,
+            NotificationKind::QuantumEnded => &mut self.quantum_ended,
+            NotificationKind::TaskPromoted => &mut self.task_promoted,
+        };
+        *flag = enabled;
+    }
+}
+
+#[derive(Clone)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+impl Notification {
+    /// Send the notification with `notify-send`, for front-ends that
+    /// don't have a GApplication to send it through
+    pub fn send_with_notify_send(&self) {
+        // Not having notify-send installed shouldn't take the app down
+        let _ = Command::new("notify-send")
+            .arg("--app-name=Nasin")
+            .arg(&self.title)
+            .arg(&self.body)
+            // Anything it prints would end up on top of the TUI
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .and_then(|mut child| child.wait());
+    }
+}
+
+/// Keeps track of what has already been announced, so each deadline
+/// and work session is only notified once.
+pub struct Notifier {
+    pub settings: NotificationSettings,
+    /// By task and deadline, so the next time a recurring task is due
+    /// it's announced again
+    notified: HashSet<(u64, DateTime<Local>, NotificationKind)>,
+    quantum_start: DateTime<Local>,
+    quantum_notified: bool,
+}
+
+impl Notifier {
+    pub fn new(settings: NotificationSettings) -> Self {
+        Notifier {
+            settings,
+            notified: HashSet::new(),
+            quantum_start: Local::now(),
+            quantum_notified: false,
+        }
+    }
+
+    /// Start timing a new work quantum, call this whenever the scheduler
+    /// moves on to a new task
+    pub fn start_quantum(&mut self, now: DateTime<Local>) {
+        self.quantum_start = now;
+        self.quantum_notified = false;
+    }
+
+    /// How much of the work quantum is left, negative once it's over
+    pub fn time_left(&self, now: DateTime<Local>) -> Duration {
+        self.quantum_start + Duration::minutes(self.settings.quantum_minutes as i64) - now
+    }
+
+    /// Check for deadlines and the end of the work quantum
+    pub fn poll(&mut self, tasks: &Tasks, now: DateTime<Local>) -> Vec<Notification> {
+        let mut out = Vec::new();
+        for task in tasks.tasks.iter().filter(|x| !x.paused) {
+            let Some(deadline) = task.deadline else {
+                continue;
+            };
+            let kind = if deadline <= now {
+                NotificationKind::DeadlinePassed
+            } else if deadline - now <= Duration::hours(self.settings.approaching_hours as i64) {
+                NotificationKind::DeadlineApproaching
+            } else {
+                continue;
+            };
+            if !self.notified.insert((task.id, deadline, kind)) {
+                continue;
+            }
+            let title = match kind {
+                NotificationKind::DeadlinePassed => format!("{} is overdue", task.name),
+                _ => format!("{} is due soon", task.name),
+            };
+            let body = format!("Deadline: {}", format_deadline(&deadline));
+            out.push(Notification { kind, title, body });
+        }
+        let quantum = Duration::minutes(self.settings.quantum_minutes as i64);
+        // Nothing is being worked on if every task is paused
+        let current = tasks.tasks.first().filter(|x| !x.paused);
+        if let Some(task) = current.filter(|_| !self.quantum_notified) {
+            if now - self.quantum_start >= quantum {
+                self.quantum_notified = true;
+                out.push(Notification {
+                    kind: NotificationKind::QuantumEnded,
+                    title: String::from("Time to switch tasks"),
+                    body: format!("Step to move on from {}", task.name),
+                });
+            }
+        }
+        out.retain(|x| self.settings.enabled(x.kind));
+        out
+    }
+
+    /// Build the notification for a task that was promoted by aging in
+    /// `Tasks::step`
+    pub fn promoted(&self, task: &Task) -> Option<Notification> {
+        if !self.settings.enabled(NotificationKind::TaskPromoted) {
+            return None;
+        }
+        Some(Notification {
+            kind: NotificationKind::TaskPromoted,
+            title: format!("{} was promoted", task.name),
+            body: format!(
+                "It has waited long enough to reach priority {}",
+                task.priority
+            ),
+        })
+    }
+}
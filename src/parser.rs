@@ -0,0 +1,400 @@
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveTime, Weekday};
+
+use crate::scheduler::{local_deadline, Changes, Energy, Recurrence, RecurrenceUnit, Task, Tasks};
+
+/// Everything that could be pulled out of a line of quick-add text
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct QuickAdd {
+    pub name: String,
+    pub priority: Option<u8>,
+    pub deadline: Option<DateTime<Local>>,
+    pub tags: Vec<String>,
+    pub recurrence: Option<Recurrence>,
+    /// Minutes of work, from something like `~2h30m`
+    pub estimate: Option<u32>,
+    /// The name of the project, from something like `+website`
+    pub project: Option<String>,
+    /// How much energy it needs, from `@low`, `@med` or `@high`
+    pub energy: Option<Energy>,
+    /// When it can be started, from something like `after friday`,
+    /// until which it's snoozed
+    pub start: Option<DateTime<Local>>,
+}
+
+impl QuickAdd {
+    pub fn into_task(self) -> Task {
+        self.into_task_or(1)
+    }
+
+    /// Make the task, with `default_priority` if none was given
+    pub fn into_task_or(self, default_priority: u8) -> Task {
+        let mut task = Task::new(
+            self.name,
+            self.priority.unwrap_or(default_priority),
+            self.deadline,
+        );
+        task.tags = self.tags;
+        task.recurrence = self.recurrence;
+        task.estimate = self.estimate;
+        task.energy = self.energy;
+        if let Some(start) = self.start {
+            task.paused = true;
+            task.snoozed_until = Some(start);
+        }
+        task
+    }
+
+    /// The id of the project named in `tasks`, which is added to them
+    /// if it's new
+    pub fn project_in(&self, tasks: &mut Tasks) -> Option<u64> {
+        self.project.as_ref().map(|x| tasks.project_named(x))
+    }
+
+    /// Changes to make to existing tasks, for anything that was given.
+    /// The name, project and start are left out.
+    pub fn into_changes(self) -> Changes {
+        Changes {
+            priority: self.priority,
+            deadline: self.deadline.map(Some),
+            tags: (!self.tags.is_empty()).then_some(self.tags),
+            estimate: self.estimate.map(Some),
+            energy: self.energy.map(Some),
+            ..Changes::default()
+        }
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_unit(word: &str) -> Option<RecurrenceUnit> {
+    match word.trim_end_matches('s') {
+        "day" => Some(RecurrenceUnit::Day),
+        "week" => Some(RecurrenceUnit::Week),
+        "month" => Some(RecurrenceUnit::Month),
+        "year" => Some(RecurrenceUnit::Year),
+        _ => None,
+    }
+}
+
+// More than this many days, weeks, months or years is taken as part of
+// the name, since dates can't go that far
+const MAX_COUNT: u32 = 1000;
+
+fn parse_number(word: &str) -> Option<u32> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "ten" => Some(10),
+        _ => word.parse().ok().filter(|x| *x <= MAX_COUNT),
+    }
+}
+
+// Estimates like 2h, 45m, 1h30m, or a number of minutes
+fn parse_estimate(word: &str) -> Option<u32> {
+    if let Ok(minutes) = word.parse() {
+        return Some(minutes);
+    }
+    let mut minutes: u32 = 0;
+    let mut number = String::new();
+    for c in word.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' if !number.is_empty() => {
+                let amount: u32 = number.parse().ok()?;
+                let amount = if c == 'h' {
+                    amount.checked_mul(60)?
+                } else {
+                    amount
+                };
+                minutes = minutes.checked_add(amount)?;
+                number.clear();
+            }
+            _ => return None,
+        }
+    }
+    number.is_empty().then_some(minutes)
+}
+
+// Times like 3pm, 3:30pm, 15:00, noon and midnight
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let (clock, offset) = if let Some(x) = word.strip_suffix("am") {
+        (x, Some(0))
+    } else if let Some(x) = word.strip_suffix("pm") {
+        (x, Some(12))
+    } else {
+        (word, None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm after it
+        None if offset.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// A day on its own at the start of `words`, like "friday", "tomorrow",
+// "next week", "in 3 days" or "2025-04-15", and how many words it took
+fn parse_day(words: &[String], now: DateTime<Local>) -> Option<(NaiveDate, usize)> {
+    let today = now.date_naive();
+    let word = words.first()?.as_str();
+    let next = words.get(1).map(String::as_str);
+    let after = words.get(2).map(String::as_str);
+    match word {
+        "today" => return Some((today, 1)),
+        "tomorrow" | "tmr" => return Some((today + Days::new(1), 1)),
+        "next" => {
+            if let Some(weekday) = next.and_then(parse_weekday) {
+                return Some((next_weekday(today, weekday), 2));
+            }
+            let unit = next.and_then(parse_unit)?;
+            let date = Recurrence { every: 1, unit }.next(now).date_naive();
+            return Some((date, 2));
+        }
+        "in" => {
+            let every = next.and_then(parse_number)?;
+            let unit = after.and_then(parse_unit)?;
+            return Some((Recurrence { every, unit }.next(now).date_naive(), 3));
+        }
+        _ => {}
+    }
+    if let Some(weekday) = parse_weekday(word) {
+        return Some((next_weekday(today, weekday), 1));
+    }
+    let date = NaiveDate::parse_from_str(word, "%Y-%m-%d").ok()?;
+    Some((date, 1))
+}
+
+// The next `weekday` strictly after `today`
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let ahead = if ahead == 0 { 7 } else { ahead };
+    today + Days::new(ahead as u64)
+}
+
+/// Parse a line like "Call dentist tomorrow 3pm !2 #health every 6 months".
+/// Anything that isn't a date, time, priority (`!N`), tag (`#tag`),
+/// project (`+name`), energy (`@low`), estimate (`~2h`), start
+/// (`after friday`) or recurrence is left as the name of the task.
+pub fn parse(text: &str, now: DateTime<Local>) -> QuickAdd {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let lower: Vec<String> = words.iter().map(|x| x.to_lowercase()).collect();
+    let today = now.date_naive();
+    let mut out = QuickAdd::default();
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+    let mut name: Vec<&str> = Vec::new();
+
+    let mut i = 0;
+    while i < words.len() {
+        let word = lower[i].as_str();
+        let next = lower.get(i + 1).map(String::as_str);
+        let after = lower.get(i + 2).map(String::as_str);
+
+        // Priority and tags
+        if let Some(priority) = word.strip_prefix('!').and_then(|x| x.parse::<u8>().ok()) {
+            if priority >= 1 {
+                out.priority = Some(priority);
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(estimate) = word
+            .strip_prefix('~')
+            .filter(|x| !x.is_empty())
+            .and_then(parse_estimate)
+        {
+            out.estimate = Some(estimate);
+            i += 1;
+            continue;
+        }
+        if let Some(tag) = words[i].strip_prefix('#').filter(|x| !x.is_empty()) {
+            out.tags.push(tag.to_string());
+            i += 1;
+            continue;
+        }
+        if let Some(energy) = word.strip_prefix('@').and_then(Energy::parse) {
+            out.energy = Some(energy);
+            i += 1;
+            continue;
+        }
+        if let Some(project) = words[i].strip_prefix('+').filter(|x| !x.is_empty()) {
+            out.project = Some(project.to_string());
+            i += 1;
+            continue;
+        }
+
+        // Recurrence
+        let simple_recurrence = match word {
+            "daily" => Some(RecurrenceUnit::Day),
+            "weekly" => Some(RecurrenceUnit::Week),
+            "monthly" => Some(RecurrenceUnit::Month),
+            "yearly" | "annually" => Some(RecurrenceUnit::Year),
+            _ => None,
+        };
+        if let Some(unit) = simple_recurrence {
+            out.recurrence = Some(Recurrence { every: 1, unit });
+            i += 1;
+            continue;
+        }
+        if word == "every" {
+            if let Some(unit) = next.and_then(parse_unit) {
+                out.recurrence = Some(Recurrence { every: 1, unit });
+                i += 2;
+                continue;
+            }
+            if let (Some(every), Some(unit)) = (
+                next.and_then(parse_number).filter(|x| *x > 0),
+                after.and_then(parse_unit),
+            ) {
+                out.recurrence = Some(Recurrence { every, unit });
+                i += 3;
+                continue;
+            }
+            // "every monday" is weekly, starting on the next monday
+            if let Some(weekday) = next.and_then(parse_weekday) {
+                out.recurrence = Some(Recurrence {
+                    every: 1,
+                    unit: RecurrenceUnit::Week,
+                });
+                date = Some(next_weekday(today, weekday));
+                i += 2;
+                continue;
+            }
+        }
+
+        // When it can be started, like "after friday" or "start in 2 weeks"
+        if matches!(word, "after" | "start") {
+            if let Some((day, len)) = parse_day(&lower[i + 1..], now) {
+                out.start = local_deadline(day, None);
+                i += len + 1;
+                continue;
+            }
+        }
+
+        // Relative dates
+        match word {
+            "today" | "tonight" => {
+                date = Some(today);
+                if word == "tonight" && time.is_none() {
+                    time = NaiveTime::from_hms_opt(20, 0, 0);
+                }
+                i += 1;
+                continue;
+            }
+            "tomorrow" | "tmr" => {
+                date = Some(today + Days::new(1));
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        if word == "in" {
+            if let (Some(amount), Some(unit)) =
+                (next.and_then(parse_number), after.and_then(parse_unit))
+            {
+                let recurrence = Recurrence {
+                    every: amount,
+                    unit,
+                };
+                date = Some(recurrence.next(now).date_naive());
+                i += 3;
+                continue;
+            }
+        }
+        if word == "next" {
+            if let Some(weekday) = next.and_then(parse_weekday) {
+                date = Some(next_weekday(today, weekday));
+                i += 2;
+                continue;
+            }
+            if let Some(unit) = next.and_then(parse_unit) {
+                date = Some(Recurrence { every: 1, unit }.next(now).date_naive());
+                i += 2;
+                continue;
+            }
+        }
+
+        // Absolute dates, weekdays and times, optionally after a preposition
+        let (skip, target) = match word {
+            "on" | "at" | "by" | "due" => (1, next),
+            _ => (0, Some(word)),
+        };
+        if let Some(target) = target {
+            // Short names like "sat" are only dates after a preposition,
+            // so they can still be used as words in the name
+            let weekday = parse_weekday(target).filter(|_| skip == 1 || target.ends_with("day"));
+            if let Some(weekday) = weekday {
+                date = Some(next_weekday(today, weekday));
+                i += skip + 1;
+                continue;
+            }
+            if let Ok(parsed) = NaiveDate::parse_from_str(target, "%Y-%m-%d") {
+                date = Some(parsed);
+                i += skip + 1;
+                continue;
+            }
+            if let Some(parsed) = parse_time(target) {
+                time = Some(parsed);
+                i += skip + 1;
+                continue;
+            }
+            // "tomorrow at 3pm" and "due tomorrow"
+            if skip == 1 && matches!(target, "today" | "tomorrow" | "tonight" | "tmr") {
+                i += 1;
+                continue;
+            }
+        }
+
+        name.push(words[i]);
+        i += 1;
+    }
+
+    // A time on its own is the next time it comes around
+    if date.is_none() {
+        if let Some(t) = time {
+            date = Some(if t > now.time() {
+                today
+            } else {
+                today + Days::new(1)
+            });
+        }
+    }
+    out.deadline = date.and_then(|x| local_deadline(x, time));
+    // "every week" with no date starts from now
+    if out.deadline.is_none() {
+        if let Some(recurrence) = out.recurrence {
+            out.deadline = Some(recurrence.next(now));
+        }
+    }
+    out.name = name.join(" ");
+    out
+}
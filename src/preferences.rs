@@ -0,0 +1,441 @@
+use std::{cell::RefCell, rc::Rc};
+
+use chrono::{NaiveDate, NaiveTime, Timelike, Weekday};
+use gtk::{glib, StringList, ToggleButton};
+use libadwaita::prelude::*;
+use libadwaita::{
+    ActionRow, ApplicationWindow, ComboRow, EntryRow, PreferencesGroup, PreferencesPage,
+    PreferencesWindow, SpinRow, SwitchRow,
+};
+use nasin::calendar::Calendar;
+use nasin::config::{is_valid_format, Config};
+use nasin::project::{FairShare, Group};
+use nasin::scheduler::{AgingPolicy, DeadlinePolicy, Tasks};
+
+use crate::settings::Settings;
+
+// A row editing a date or time format, only saved once it's usable
+fn format_row(
+    settings: &Rc<Settings>,
+    title: &str,
+    get: fn(&Config) -> &String,
+    set: fn(&mut Config, String),
+) -> EntryRow {
+    let row = EntryRow::builder()
+        .title(title)
+        .text(get(&settings.config()).as_str())
+        .show_apply_button(true)
+        .build();
+    row.connect_apply(glib::clone!(@strong settings => move |row| {
+        let text = row.text().to_string();
+        if is_valid_format(&text) && !text.is_empty() {
+            row.remove_css_class("error");
+            settings.update(|config| set(config, text));
+        } else {
+            row.add_css_class("error");
+        }
+    }));
+    row
+}
+
+// A switch for whether an action asks before going ahead
+fn confirm_row(
+    settings: &Rc<Settings>,
+    title: &str,
+    get: fn(&Config) -> bool,
+    set: fn(&mut Config, bool),
+) -> SwitchRow {
+    let row = SwitchRow::builder()
+        .title(title)
+        .active(get(&settings.config()))
+        .build();
+    row.connect_active_notify(glib::clone!(@strong settings => move |row| {
+        let active = row.is_active();
+        settings.update(|config| set(config, active));
+    }));
+    row
+}
+
+// A row editing one number in the aging policy, saved as soon as it changes
+fn aging_row(
+    tasks: &Rc<RefCell<Tasks>>,
+    title: &str,
+    subtitle: &str,
+    range: (u32, u32),
+    get: fn(&AgingPolicy) -> u32,
+    set: fn(&mut AgingPolicy, u32),
+) -> SpinRow {
+    let row = SpinRow::with_range(range.0 as f64, range.1 as f64, 1.0);
+    row.set_title(title);
+    row.set_subtitle(subtitle);
+    row.set_value(get(&tasks.borrow().aging_policy) as f64);
+    let tasks = tasks.clone();
+    row.connect_value_notify(move |row| {
+        let mut tasks = tasks.borrow_mut();
+        let mut policy = tasks.aging_policy;
+        set(&mut policy, row.value() as u32);
+        if policy != tasks.aging_policy {
+            tasks.set_aging_policy(policy);
+        }
+    });
+    row
+}
+
+// Change the deadline policy, only saving it if something is different
+fn update_deadline_policy(tasks: &Rc<RefCell<Tasks>>, f: impl FnOnce(&mut DeadlinePolicy)) {
+    let mut tasks = tasks.borrow_mut();
+    let mut policy = tasks.deadline_policy.clone();
+    f(&mut policy);
+    if policy != tasks.deadline_policy {
+        tasks.set_deadline_policy(policy);
+    }
+}
+
+// Change the working hours calendar, if there is one
+fn update_calendar(tasks: &Rc<RefCell<Tasks>>, f: impl FnOnce(&mut Calendar)) {
+    update_deadline_policy(tasks, |policy| {
+        if let Some(calendar) = policy.calendar.as_mut() {
+            f(calendar);
+        }
+    });
+}
+
+// A row for the hour the working day starts or ends
+fn hour_row(
+    tasks: &Rc<RefCell<Tasks>>,
+    title: &str,
+    get: fn(&Calendar) -> NaiveTime,
+    set: fn(&mut Calendar, NaiveTime),
+) -> SpinRow {
+    let row = SpinRow::with_range(0.0, 23.0, 1.0);
+    row.set_title(title);
+    let calendar = tasks.borrow().deadline_policy.calendar.clone();
+    row.set_value(get(&calendar.unwrap_or_default()).hour() as f64);
+    let tasks = tasks.clone();
+    row.connect_value_notify(move |row| {
+        if let Some(time) = NaiveTime::from_hms_opt(row.value() as u32, 0, 0) {
+            update_calendar(&tasks, |calendar| set(calendar, time));
+        }
+    });
+    row
+}
+
+// Holidays written as dates separated by commas or spaces
+fn parse_holidays(text: &str) -> Option<Vec<NaiveDate>> {
+    text.split(|x: char| x == ',' || x.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| NaiveDate::parse_from_str(x, "%Y-%m-%d").ok())
+        .collect()
+}
+
+// The group for how deadlines turn into priorities, and the hours that
+// count towards them
+fn deadline_group(tasks: &Rc<RefCell<Tasks>>) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Deadlines")
+        .description("How close a deadline has to be to make a task more urgent")
+        .build();
+    let policy = tasks.borrow().deadline_policy.clone();
+    let calendar = policy.calendar.clone().unwrap_or_default();
+
+    let level_row = SpinRow::with_range(1.0, 1000.0, 1.0);
+    level_row.set_title("Hours per Priority Level");
+    level_row.set_subtitle("Each priority level stands for this many hours left");
+    level_row.set_value(policy.hours_per_level as f64);
+    level_row.connect_value_notify(glib::clone!(@strong tasks => move |row| {
+        let hours = row.value() as u32;
+        update_deadline_policy(&tasks, |policy| policy.hours_per_level = hours);
+    }));
+    group.add(&level_row);
+
+    let overdue_row = SwitchRow::builder()
+        .title("Overdue Tasks Go First")
+        .subtitle("Put overdue tasks ahead of everything else, rather than at priority 1")
+        .active(policy.overdue_preempts)
+        .build();
+    overdue_row.connect_active_notify(glib::clone!(@strong tasks => move |row| {
+        let active = row.is_active();
+        update_deadline_policy(&tasks, |policy| policy.overdue_preempts = active);
+    }));
+    group.add(&overdue_row);
+
+    let working_row = SwitchRow::builder()
+        .title("Only Count Working Hours")
+        .subtitle("Leave out nights, weekends and holidays")
+        .active(policy.calendar.is_some())
+        .build();
+    working_row.connect_active_notify(glib::clone!(@strong tasks => move |row| {
+        let active = row.is_active();
+        update_deadline_policy(&tasks, |policy| {
+            policy.calendar = active.then(|| policy.calendar.take().unwrap_or_default());
+        });
+    }));
+    group.add(&working_row);
+
+    let days_row = ActionRow::builder().title("Working Days").build();
+    let days_box = gtk::Box::builder()
+        .spacing(3)
+        .valign(gtk::Align::Center)
+        .build();
+    let days = [
+        (Weekday::Mon, "Mo"),
+        (Weekday::Tue, "Tu"),
+        (Weekday::Wed, "We"),
+        (Weekday::Thu, "Th"),
+        (Weekday::Fri, "Fr"),
+        (Weekday::Sat, "Sa"),
+        (Weekday::Sun, "Su"),
+    ];
+    for (day, label) in days {
+        let button = ToggleButton::builder()
+            .label(label)
+            .active(calendar.working_days.contains(&day))
+            .css_classes(vec!["circular"])
+            .build();
+        button.connect_toggled(glib::clone!(@strong tasks => move |button| {
+            let active = button.is_active();
+            update_calendar(&tasks, |calendar| {
+                calendar.working_days.retain(|x| *x != day);
+                if active {
+                    calendar.working_days.push(day);
+                }
+            });
+        }));
+        days_box.append(&button);
+    }
+    days_row.add_suffix(&days_box);
+    group.add(&days_row);
+
+    let start_row = hour_row(
+        tasks,
+        "Start of the Day",
+        |x| x.start,
+        |x, time| x.start = time,
+    );
+    let end_row = hour_row(tasks, "End of the Day", |x| x.end, |x, time| x.end = time);
+    // The day has to end after it starts
+    start_row.adjustment().set_upper(end_row.value() - 1.0);
+    end_row.adjustment().set_lower(start_row.value() + 1.0);
+    start_row.connect_value_notify(glib::clone!(@weak end_row => move |row| {
+        end_row.adjustment().set_lower(row.value() + 1.0);
+    }));
+    end_row.connect_value_notify(glib::clone!(@weak start_row => move |row| {
+        start_row.adjustment().set_upper(row.value() - 1.0);
+    }));
+    group.add(&start_row);
+    group.add(&end_row);
+
+    let holidays: Vec<String> = calendar.holidays.iter().map(|x| x.to_string()).collect();
+    let holidays_row = EntryRow::builder()
+        .title("Holidays, like 2025-12-25, 2025-12-26")
+        .text(holidays.join(", "))
+        .show_apply_button(true)
+        .build();
+    holidays_row.connect_apply(glib::clone!(@strong tasks => move |row| {
+        match parse_holidays(&row.text()) {
+            Some(holidays) => {
+                row.remove_css_class("error");
+                update_calendar(&tasks, |calendar| calendar.holidays = holidays);
+            }
+            None => row.add_css_class("error"),
+        }
+    }));
+    group.add(&holidays_row);
+
+    for row in [
+        days_row.upcast::<gtk::Widget>(),
+        start_row.upcast(),
+        end_row.upcast(),
+        holidays_row.upcast(),
+    ] {
+        working_row
+            .bind_property("active", &row, "sensitive")
+            .sync_create()
+            .build();
+    }
+    group
+}
+
+fn fair_share_group(tasks: &Rc<RefCell<Tasks>>) -> PreferencesGroup {
+    let group = PreferencesGroup::builder()
+        .title("Fair Share")
+        .description(
+            "Work on whatever has had the least of its share lately, then by priority within it",
+        )
+        .build();
+    let choices = [FairShare::Off, FairShare::Projects, FairShare::Tags];
+    let labels: Vec<&str> = choices.iter().map(|x| x.label()).collect();
+    let share_row = ComboRow::builder()
+        .title("Share Time Between")
+        .model(&StringList::new(&labels))
+        .selected(
+            choices
+                .iter()
+                .position(|x| *x == tasks.borrow().fair_share)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    share_row.connect_selected_notify(glib::clone!(@strong tasks => move |row| {
+        let fair_share = choices[row.selected() as usize % choices.len()];
+        if fair_share != tasks.borrow().fair_share {
+            tasks.borrow_mut().set_fair_share(fair_share);
+        }
+    }));
+    group.add(&share_row);
+
+    // A share for each tag in use, only shown when sharing between tags.
+    // Projects have theirs in the project dialog.
+    let mut tags: Vec<String> = tasks
+        .borrow()
+        .tasks
+        .iter()
+        .flat_map(|x| x.tags.clone())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    for tag in tags {
+        let row = SpinRow::with_range(1.0, 100.0, 1.0);
+        row.set_title(&format!("#{}", tag));
+        row.set_subtitle("Share of time");
+        row.set_value(tasks.borrow().weight(&Group::Tag(tag.clone())) as f64);
+        row.connect_value_notify(glib::clone!(@strong tasks => move |row| {
+            tasks.borrow_mut().set_tag_weight(&tag, row.value() as u32);
+        }));
+        share_row
+            .bind_property("selected", &row, "visible")
+            .transform_to(|_, x: u32| Some(x == 2))
+            .sync_create()
+            .build();
+        group.add(&row);
+    }
+    group
+}
+
+/// Show the preferences window, with the app's settings and the
+/// settings stored with the list
+pub fn show_preferences(
+    parent: &ApplicationWindow,
+    tasks: Rc<RefCell<Tasks>>,
+    settings: Rc<Settings>,
+) -> PreferencesWindow {
+    let format_group = PreferencesGroup::builder()
+        .title("Dates")
+        .description("In strftime format, like %Y-%m-%d")
+        .build();
+    format_group.add(&format_row(
+        &settings,
+        "Date Format",
+        |x| &x.date_format,
+        |x, value| x.date_format = value,
+    ));
+    format_group.add(&format_row(
+        &settings,
+        "Time Format",
+        |x| &x.time_format,
+        |x, value| x.time_format = value,
+    ));
+
+    let tasks_group = PreferencesGroup::builder().title("Tasks").build();
+    let priority_row = SpinRow::with_range(1.0, u8::MAX as f64, 1.0);
+    priority_row.set_title("Default Priority");
+    priority_row.set_subtitle("For new tasks that aren't given one");
+    priority_row.set_value(settings.config().default_priority as f64);
+    priority_row.connect_value_notify(glib::clone!(@strong settings => move |row| {
+        let priority = row.value() as u8;
+        settings.update(|config| config.default_priority = priority);
+    }));
+    tasks_group.add(&priority_row);
+
+    let confirm_group = PreferencesGroup::builder()
+        .title("Confirmation")
+        .description("Ask before doing these")
+        .build();
+    confirm_group.add(&confirm_row(
+        &settings,
+        "Removing a Task",
+        |x| x.confirm.remove,
+        |x, value| x.confirm.remove = value,
+    ));
+    confirm_group.add(&confirm_row(
+        &settings,
+        "Finishing a Task",
+        |x| x.confirm.finish,
+        |x, value| x.confirm.finish = value,
+    ));
+    confirm_group.add(&confirm_row(
+        &settings,
+        "Changing Several Tasks",
+        |x| x.confirm.bulk,
+        |x, value| x.confirm.bulk = value,
+    ));
+
+    let general_page = PreferencesPage::builder()
+        .title("General")
+        .icon_name("preferences-system-symbolic")
+        .build();
+    general_page.add(&format_group);
+    general_page.add(&tasks_group);
+    general_page.add(&confirm_group);
+
+    let aging_group = PreferencesGroup::builder()
+        .title("Aging")
+        .description("How tasks that are waiting get promoted so they aren't starved")
+        .build();
+    aging_group.add(&aging_row(
+        &tasks,
+        "Aging Increment",
+        "How much older each waiting task gets per step",
+        (0, 100),
+        |x| x.increment,
+        |x, value| x.increment = value,
+    ));
+    aging_group.add(&aging_row(
+        &tasks,
+        "Promotion Threshold",
+        "How old a task has to be before it can be promoted",
+        (0, 1000),
+        |x| x.threshold,
+        |x, value| x.threshold = value,
+    ));
+    aging_group.add(&aging_row(
+        &tasks,
+        "Promotions per Step",
+        "How many of the oldest tasks are promoted each step",
+        (0, 100),
+        |x| x.boosted_per_step,
+        |x, value| x.boosted_per_step = value,
+    ));
+    aging_group.add(&aging_row(
+        &tasks,
+        "Promotion Amount",
+        "How many priority levels each promotion is worth",
+        (0, u8::MAX as u32),
+        |x| x.boost as u32,
+        |x, value| x.boost = value as u8,
+    ));
+    aging_group.add(&aging_row(
+        &tasks,
+        "Maximum Promotion",
+        "How far above its usual priority a task can be promoted",
+        (0, u8::MAX as u32),
+        |x| x.max_boost as u32,
+        |x, value| x.max_boost = value as u8,
+    ));
+
+    let scheduler_page = PreferencesPage::builder()
+        .title("Scheduler")
+        .icon_name("view-list-symbolic")
+        .build();
+    scheduler_page.add(&aging_group);
+    scheduler_page.add(&deadline_group(&tasks));
+    scheduler_page.add(&fair_share_group(&tasks));
+
+    let window = PreferencesWindow::builder()
+        .transient_for(parent)
+        .modal(true)
+        .build();
+    window.add(&general_page);
+    window.add(&scheduler_page);
+    window.present();
+    window
+}
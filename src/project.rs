@@ -0,0 +1,141 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{DeadlinePolicy, Task, Tasks};
+
+/// Colours a project can be shown in, by name
+pub const COLORS: [(&str, &str); 8] = [
+    ("Blue", "#3584e4"),
+    ("Green", "#33d17a"),
+    ("Yellow", "#f6d32d"),
+    ("Orange", "#ff7800"),
+    ("Red", "#e01b24"),
+    ("Purple", "#9141ac"),
+    ("Brown", "#986a44"),
+    ("Grey", "#77767b"),
+];
+
+/// What time is shared out between, so that no group of tasks gets
+/// more than its share
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum FairShare {
+    /// Go by priority alone
+    #[default]
+    Off,
+    Projects,
+    /// Tasks are counted under their first tag
+    Tags,
+}
+
+impl FairShare {
+    pub fn next(&self) -> Self {
+        match self {
+            FairShare::Off => FairShare::Projects,
+            FairShare::Projects => FairShare::Tags,
+            FairShare::Tags => FairShare::Off,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FairShare::Off => "Nothing",
+            FairShare::Projects => "Projects",
+            FairShare::Tags => "Tags",
+        }
+    }
+}
+
+/// A group of tasks that time is shared between
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Group {
+    Project(u64),
+    Tag(String),
+    /// Tasks without a project, or without tags
+    Other,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Which tasks to show, by project
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filter {
+    All,
+    Project(u64),
+    NoProject,
+}
+
+impl Filter {
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Project(id) => task.project == Some(*id),
+            Filter::NoProject => task.project.is_none(),
+        }
+    }
+
+    /// The filter after this one, going through every project that
+    /// isn't archived
+    pub fn next(&self, tasks: &Tasks) -> Self {
+        let mut filters = vec![Filter::All];
+        filters.extend(
+            tasks
+                .projects
+                .iter()
+                .filter(|x| !x.archived)
+                .map(|x| Filter::Project(x.id)),
+        );
+        filters.push(Filter::NoProject);
+        match filters.iter().position(|x| x == self) {
+            Some(i) => filters[(i + 1) % filters.len()],
+            None => Filter::All,
+        }
+    }
+}
+
+/// A bigger effort that tasks can belong to
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Project {
+    /// Assigned when the project is added to a list
+    #[serde(default)]
+    pub id: u64,
+    pub name: String,
+    /// Which project goes first when time is balanced between them and
+    /// they have had the same share
+    pub priority: u8,
+    pub deadline: Option<DateTime<Local>>,
+    /// Shown next to its tasks, like `#3584e4`
+    pub color: String,
+    /// How much time it gets compared to other projects when time is
+    /// shared between them
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Finished with, so its tasks are paused and it is kept out of the
+    /// way
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl Project {
+    pub fn new(name: String) -> Self {
+        Project {
+            id: 0,
+            name,
+            priority: 1,
+            deadline: None,
+            color: String::from(COLORS[0].1),
+            weight: 1,
+            archived: false,
+        }
+    }
+
+    /// How urgent the project is, from its priority or its deadline,
+    /// whichever is more pressing
+    pub fn urgency(&self, policy: &DeadlinePolicy, now: DateTime<Local>) -> u8 {
+        match self.deadline {
+            Some(deadline) => self.priority.min(policy.priority(&deadline, &now)),
+            None => self.priority,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use gtk::{glib, Align, Button, Label, ListBox, ListBoxRow, Orientation, StringList};
+use libadwaita::prelude::*;
+use libadwaita::{
+    ApplicationWindow, ComboRow, EntryRow, HeaderBar, PreferencesGroup, SpinRow, SwitchRow,
+};
+use nasin::project::{Filter, Project, COLORS};
+use nasin::scheduler::{format_deadline, parse_deadline, Tasks};
+
+/// The filter for a row of the sidebar
+pub fn filter_at(tasks: &Tasks, index: usize) -> Filter {
+    match index {
+        0 => Filter::All,
+        i => match tasks.projects.get(i - 1) {
+            Some(project) => Filter::Project(project.id),
+            None => Filter::NoProject,
+        },
+    }
+}
+
+/// A coloured dot to show next to things in a project
+pub fn color_dot(project: &Project) -> Label {
+    Label::builder()
+        .use_markup(true)
+        .label(format!(
+            "<span foreground=\"{}\">●</span>",
+            glib::markup_escape_text(&project.color)
+        ))
+        .tooltip_text(project.name.as_str())
+        .build()
+}
+
+fn sidebar_row(title: &str, project: Option<&Project>) -> ListBoxRow {
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(8)
+        .build();
+    if let Some(project) = project {
+        content.append(&color_dot(project));
+    }
+    content.append(
+        &Label::builder()
+            .label(title)
+            .halign(Align::Start)
+            .hexpand(true)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build(),
+    );
+    if let Some(project) = project {
+        content.append(
+            &Button::builder()
+                .icon_name("document-edit-symbolic")
+                .tooltip_text("Edit Project")
+                .css_classes(vec!["flat"])
+                .action_name("app.edit-project")
+                .action_target(&project.id.to_variant())
+                .build(),
+        );
+    }
+    let row = ListBoxRow::builder().child(&content).build();
+    if project.is_some_and(|x| x.archived) {
+        row.add_css_class("dim-label");
+    }
+    row
+}
+
+/// Fill the sidebar with all tasks, each project, then the tasks
+/// without one, and select the row for `filter`
+pub fn build_sidebar(sidebar: &ListBox, tasks: &Tasks, filter: Filter) {
+    sidebar.remove_all();
+    sidebar.append(&sidebar_row("All Tasks", None));
+    for project in &tasks.projects {
+        sidebar.append(&sidebar_row(&project.name, Some(project)));
+    }
+    sidebar.append(&sidebar_row("No Project", None));
+    let selected = (0..=tasks.projects.len() + 1)
+        .find(|x| filter_at(tasks, *x) == filter)
+        .unwrap_or(0);
+    sidebar.select_row(sidebar.row_at_index(selected as i32).as_ref());
+}
+
+/// Edit a project, or a new one if it has no id yet. `then` is given
+/// the project when it's saved, and `remove` is called if it's removed.
+pub fn show_project_dialog(
+    parent: &ApplicationWindow,
+    project: Project,
+    then: impl Fn(Project) + 'static,
+    remove: impl Fn() + 'static,
+) {
+    let is_new = project.id == 0;
+    let name_row = EntryRow::builder()
+        .title("Name")
+        .text(project.name.as_str())
+        .build();
+    let priority_row = SpinRow::with_range(1.0, u8::MAX as f64, 1.0);
+    priority_row.set_title("Priority");
+    priority_row.set_subtitle("Which project goes first when time is shared");
+    priority_row.set_value(project.priority as f64);
+    let weight_row = SpinRow::with_range(1.0, 100.0, 1.0);
+    weight_row.set_title("Share");
+    weight_row.set_subtitle("How much time it gets compared to other projects");
+    weight_row.set_value(project.weight.max(1) as f64);
+    let deadline_row = EntryRow::builder()
+        .title("Deadline, like 2025-03-10 17:00")
+        .text(
+            project
+                .deadline
+                .map(|x| format_deadline(&x))
+                .unwrap_or_default(),
+        )
+        .build();
+    let color_names: Vec<&str> = COLORS.iter().map(|x| x.0).collect();
+    let color_row = ComboRow::builder()
+        .title("Colour")
+        .model(&StringList::new(&color_names))
+        .selected(
+            COLORS
+                .iter()
+                .position(|x| x.1 == project.color)
+                .unwrap_or(0) as u32,
+        )
+        .build();
+    let archived_row = SwitchRow::builder()
+        .title("Archived")
+        .subtitle("Pause its tasks and keep it out of the way")
+        .active(project.archived)
+        .visible(!is_new)
+        .build();
+    let group = PreferencesGroup::new();
+    group.add(&name_row);
+    group.add(&priority_row);
+    group.add(&weight_row);
+    group.add(&deadline_row);
+    group.add(&color_row);
+    group.add(&archived_row);
+
+    let save_button = Button::builder()
+        .label(if is_new { "Add" } else { "Save" })
+        .css_classes(vec!["suggested-action"])
+        .build();
+    let remove_button = Button::builder()
+        .label("Remove")
+        .css_classes(vec!["destructive-action"])
+        .visible(!is_new)
+        .build();
+    let header_bar = HeaderBar::new();
+    header_bar.pack_end(&save_button);
+    header_bar.pack_start(&remove_button);
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    content.append(&group);
+    let holder_box = gtk::Box::new(Orientation::Vertical, 0);
+    holder_box.append(&header_bar);
+    holder_box.append(&content);
+    let window = ApplicationWindow::builder()
+        .title(if is_new {
+            "New Project"
+        } else {
+            "Edit Project"
+        })
+        .transient_for(parent)
+        .modal(true)
+        .default_width(400)
+        .content(&holder_box)
+        .build();
+
+    save_button.connect_clicked(glib::clone!(@weak window => move |_| {
+        let name = name_row.text().trim().to_string();
+        if name.is_empty() {
+            name_row.add_css_class("error");
+            return;
+        }
+        let text = deadline_row.text();
+        let deadline = match text.trim() {
+            "" => None,
+            text => match parse_deadline(text) {
+                Some(deadline) => Some(deadline),
+                None => {
+                    deadline_row.add_css_class("error");
+                    return;
+                }
+            },
+        };
+        then(Project {
+            id: project.id,
+            name,
+            priority: priority_row.value() as u8,
+            deadline,
+            color: String::from(COLORS[color_row.selected() as usize % COLORS.len()].1),
+            weight: weight_row.value() as u32,
+            archived: archived_row.is_active(),
+        });
+        window.close();
+    }));
+    remove_button.connect_clicked(glib::clone!(@weak window => move |_| {
+        remove();
+        window.close();
+    }));
+    window.present();
+}
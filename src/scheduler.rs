@@ -1,28 +1,338 @@
-use std::{cmp::Ordering, fs::File, path::PathBuf};
+use std::{cmp::Ordering, collections::BTreeMap, rc::Rc};
 
-use chrono::{DateTime, Local};
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeZone,
+};
 use serde::{Deserialize, Serialize};
 
-fn get_data_path() -> PathBuf {
-    let mut path = dirs::data_dir().unwrap();
-    path.push("nasin");
-    if !path.exists() {
-        std::fs::create_dir_all(path.clone()).unwrap();
+use crate::calendar::Calendar;
+use crate::project::{FairShare, Group, Project, COLORS};
+use crate::storage::{FileStorage, MemoryStorage, Storage};
+
+/// How long a task is worked on before stepping, unless configured
+pub const DEFAULT_QUANTUM_MINUTES: u32 = 25;
+
+// How many of the latest steps count when sharing time between
+// groups, so one that was busy long ago doesn't lose out for good
+const BALANCE_WINDOW: usize = 100;
+
+/// How many events the history keeps before dropping the oldest, so
+/// the saved list doesn't grow forever
+pub const HISTORY_LIMIT: usize = 5000;
+
+/// How tasks waiting to run are aged and promoted at each step
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgingPolicy {
+    /// How much older each waiting task gets per step
+    pub increment: u32,
+    /// How many priority levels a promotion is worth
+    pub boost: u8,
+    /// How old a task has to be before it can be promoted
+    pub threshold: u32,
+    /// How many of the oldest tasks are promoted per step
+    pub boosted_per_step: u32,
+    /// How far above its base priority a task can be promoted
+    pub max_boost: u8,
+}
+
+impl Default for AgingPolicy {
+    fn default() -> Self {
+        AgingPolicy {
+            increment: 1,
+            boost: 1,
+            threshold: 1,
+            boosted_per_step: 1,
+            max_boost: u8::MAX,
+        }
     }
-    path.push("tasks");
-    path.set_extension("json");
-    if !path.exists() {
-        File::create(path.clone()).unwrap();
+}
+
+/// Common times to snooze a task until
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Snooze {
+    /// A few hours from now
+    LaterToday,
+    /// The start of tomorrow morning
+    Tomorrow,
+    /// The start of next Monday morning
+    NextWeek,
+}
+
+// When "tomorrow" and "next week" start
+const MORNING_HOUR: u32 = 9;
+
+impl Snooze {
+    pub const ALL: [Snooze; 3] = [Snooze::LaterToday, Snooze::Tomorrow, Snooze::NextWeek];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Snooze::LaterToday => "Later Today",
+            Snooze::Tomorrow => "Tomorrow",
+            Snooze::NextWeek => "Next Week",
+        }
+    }
+
+    /// When a task snoozed at `now` wakes up
+    pub fn until(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let today = now.date_naive();
+        let days = match self {
+            Snooze::LaterToday => return now + Duration::hours(3),
+            Snooze::Tomorrow => 1,
+            Snooze::NextWeek => 7 - today.weekday().num_days_from_monday() as u64,
+        };
+        let morning = NaiveTime::from_hms_opt(MORNING_HOUR, 0, 0);
+        local_deadline(today + Days::new(days), morning).unwrap_or(now)
     }
-    path
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+/// Turn a wall clock time into an actual time in `tz`. If the time is
+/// repeated by a DST change the first one is used, and if it is skipped
+/// over the time an hour later is used.
+pub fn resolve_local<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> Option<DateTime<Tz>> {
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(x) => Some(x),
+        LocalResult::Ambiguous(x, _) => Some(x),
+        LocalResult::None => tz
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest(),
+    }
+}
+
+/// Deadline at `time` on `date`, or the start of the day without a time
+pub fn local_deadline(date: NaiveDate, time: Option<NaiveTime>) -> Option<DateTime<Local>> {
+    resolve_local(&Local, date.and_time(time.unwrap_or(NaiveTime::MIN)))
+}
+
+/// Parse a deadline written as `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`
+pub fn parse_deadline(text: &str) -> Option<DateTime<Local>> {
+    let text = text.trim();
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M") {
+        return resolve_local(&Local, datetime);
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+    local_deadline(date, None)
+}
+
+/// Show a deadline, leaving out the time for ones that are just a date
+pub fn format_deadline(deadline: &DateTime<Local>) -> String {
+    format_deadline_with(deadline, "%Y-%m-%d", "%H:%M")
+}
+
+/// Show a deadline with the given date and time formats, leaving out
+/// the time for ones that are just a date
+pub fn format_deadline_with(
+    deadline: &DateTime<Local>,
+    date_format: &str,
+    time_format: &str,
+) -> String {
+    if deadline.time() == NaiveTime::MIN {
+        deadline.format(date_format).to_string()
+    } else {
+        deadline
+            .format(&format!("{} {}", date_format, time_format))
+            .to_string()
+    }
+}
+
+/// Rough human readable length of time, like "3 days"
+pub fn format_duration(duration: Duration) -> String {
+    let (amount, unit) = if duration.num_days() > 0 {
+        (duration.num_days(), "day")
+    } else if duration.num_hours() > 0 {
+        (duration.num_hours(), "hour")
+    } else {
+        (duration.num_minutes(), "minute")
+    };
+    if amount == 1 {
+        format!("{} {}", amount, unit)
+    } else {
+        format!("{} {}s", amount, unit)
+    }
+}
+
+/// Show the time left in a work session like a countdown, `12:05`,
+/// with a minus sign once it has run over
+pub fn format_timer(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let seconds = duration.num_seconds().abs();
+    format!("{}{}:{:02}", sign, seconds / 60, seconds % 60)
+}
+
+/// Show an estimate of work left, like `2h30m`, the same way it is
+/// written in quick add
+pub fn format_estimate(minutes: u64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// How a deadline turns into a priority. The closer the deadline, the
+/// higher the priority (lower number).
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadlinePolicy {
+    /// Give overdue tasks priority 0, ahead of everything else
+    pub overdue_preempts: bool,
+    /// How many hours until the deadline each priority level stands for
+    pub hours_per_level: u32,
+    /// Only count the hours in this calendar, rather than every hour
+    pub calendar: Option<Calendar>,
+}
+
+impl Default for DeadlinePolicy {
+    fn default() -> Self {
+        DeadlinePolicy {
+            overdue_preempts: true,
+            hours_per_level: 24,
+            calendar: None,
+        }
+    }
+}
+
+impl DeadlinePolicy {
+    /// Priority for a task due at `deadline`. This works off of the
+    /// actual time left, so time zone and DST changes between now and
+    /// the deadline don't shift it. With a calendar only working time
+    /// counts.
+    pub fn priority<Tz: TimeZone>(&self, deadline: &DateTime<Tz>, now: &DateTime<Tz>) -> u8 {
+        if deadline <= now {
+            return if self.overdue_preempts { 0 } else { 1 };
+        }
+        let minutes = self.minutes_left(deadline, now);
+        let minutes_per_level = self.hours_per_level.max(1) as i64 * 60;
+        (minutes / minutes_per_level + 1).clamp(1, u8::MAX as i64) as u8
+    }
+
+    /// Minutes from `now` until `deadline` that count towards it, which
+    /// is only working time if there is a calendar
+    pub fn minutes_left<Tz: TimeZone>(&self, deadline: &DateTime<Tz>, now: &DateTime<Tz>) -> i64 {
+        // Without working hours every deadline would look like it has
+        // no time left, so count every hour instead
+        match self.calendar.as_ref().filter(|x| x.has_working_hours()) {
+            Some(calendar) => calendar.working_minutes(now, deadline),
+            None => deadline
+                .clone()
+                .signed_duration_since(now.clone())
+                .num_minutes()
+                .max(0),
+        }
+    }
+}
+
+/// How much focus a task needs, or how much there is to give
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Energy {
+    Low,
+    Medium,
+    High,
+}
+
+impl Energy {
+    pub const ALL: [Energy; 3] = [Energy::Low, Energy::Medium, Energy::High];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Energy::Low => "Low",
+            Energy::Medium => "Medium",
+            Energy::High => "High",
+        }
+    }
+
+    /// Parse a word like "low", "med" or "high"
+    pub fn parse(word: &str) -> Option<Self> {
+        match word.to_lowercase().as_str() {
+            "low" => Some(Energy::Low),
+            "med" | "medium" => Some(Energy::Medium),
+            "high" => Some(Energy::High),
+            _ => None,
+        }
+    }
+
+    // How many levels apart two energies are
+    fn distance(&self, other: &Energy) -> u8 {
+        (*self as u8).abs_diff(*other as u8)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum RecurrenceUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// A task that comes back after being finished, due again `every` units
+/// after its last deadline
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// At least 1, or it would never move on
+    pub every: u32,
+    pub unit: RecurrenceUnit,
+}
+
+impl Recurrence {
+    pub fn next(&self, from: DateTime<Local>) -> DateTime<Local> {
+        let date = from.date_naive();
+        let next_date = match self.unit {
+            RecurrenceUnit::Day => date.checked_add_days(chrono::Days::new(self.every as u64)),
+            RecurrenceUnit::Week => date.checked_add_days(chrono::Days::new(self.every as u64 * 7)),
+            RecurrenceUnit::Month => date.checked_add_months(Months::new(self.every)),
+            RecurrenceUnit::Year => self
+                .every
+                .checked_mul(12)
+                .and_then(|x| date.checked_add_months(Months::new(x))),
+        };
+        next_date
+            .and_then(|x| resolve_local(&Local, x.and_time(from.time())))
+            .unwrap_or(from)
+    }
+
+    pub fn describe(&self) -> String {
+        let unit = match self.unit {
+            RecurrenceUnit::Day => "day",
+            RecurrenceUnit::Week => "week",
+            RecurrenceUnit::Month => "month",
+            RecurrenceUnit::Year => "year",
+        };
+        if self.every == 1 {
+            format!("every {}", unit)
+        } else {
+            format!("every {} {}s", self.every, unit)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Task {
+    /// Assigned when the task is added to a list
+    #[serde(default)]
+    pub id: u64,
     pub name: String,
     pub priority: u8,
     pub paused: bool,
     pub deadline: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// The task is paused until then, after being snoozed or because
+    /// it can't be started before
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Local>>,
+    /// Minutes of work left on the task, if the user knows
+    #[serde(default)]
+    pub estimate: Option<u32>,
+    /// The id of the project the task belongs to
+    #[serde(default)]
+    pub project: Option<u64>,
+    /// How much energy the task needs, if it matters
+    #[serde(default)]
+    pub energy: Option<Energy>,
     age: u32,
     base_priority: u8,
 }
@@ -30,30 +340,58 @@ pub struct Task {
 impl Task {
     pub fn new(name: String, priority: u8, deadline: Option<DateTime<Local>>) -> Task {
         let new_priority = deadline
-            .map(Task::priority_from_deadline)
+            .map(|x| DeadlinePolicy::default().priority(&x, &Local::now()))
             .unwrap_or(priority);
         Task {
+            id: 0,
             name,
             priority: new_priority,
             deadline,
             paused: false,
+            tags: Vec::new(),
+            recurrence: None,
+            snoozed_until: None,
+            estimate: None,
+            project: None,
+            energy: None,
             base_priority: new_priority,
             age: 0,
         }
     }
 
-    fn priority_from_deadline(deadline: DateTime<Local>) -> u8 {
-        let diff = deadline - Local::now();
-        (diff.num_days() + 1).min(u8::MAX as i64).max(1) as u8
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.deadline.is_some_and(|x| x <= now)
+    }
+
+    /// How long ago the deadline passed, if it has
+    pub fn overdue_by(&self, now: DateTime<Local>) -> Option<Duration> {
+        self.deadline.filter(|x| *x <= now).map(|x| now - x)
     }
 
-    pub fn age(&mut self) {
+    /// Promote the task, as it has waited long enough
+    pub fn age(&mut self, policy: &AgingPolicy) {
+        // Priority 0 is only for overdue tasks, so aging stops at 1
         if !self.paused {
             self.age = 0;
-            self.priority = std::cmp::max(self.priority - 1, 1);
+            let promoted = self
+                .priority
+                .saturating_sub(policy.boost)
+                .max(self.base_priority.saturating_sub(policy.max_boost))
+                .max(1);
+            self.priority = self.priority.min(promoted);
         }
     }
 
+    /// The priority the task goes back to after being worked on
+    pub fn base_priority(&self) -> u8 {
+        self.base_priority
+    }
+
+    /// Steps since the task was last worked on or promoted
+    pub fn steps_waited(&self) -> u32 {
+        self.age
+    }
+
     pub fn reset(&mut self) {
         self.age = 0;
         self.priority = self.base_priority;
@@ -93,9 +431,130 @@ impl Ord for Task {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum EventKind {
+    Added,
+    /// The task was worked on and put back in the queue
+    Stepped,
+    Finished,
+    Removed,
+    /// The task was bumped up a priority level by aging
+    Promoted,
+    Paused,
+    Resumed,
+}
+
+/// Something that happened to a task, kept around for statistics
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub time: DateTime<Local>,
+    pub kind: EventKind,
+    pub task_id: u64,
+    pub name: String,
+    pub base_priority: u8,
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub project: Option<u64>,
+    /// How many steps the task had gone without being worked on
+    pub steps_waited: u32,
+}
+
+impl Event {
+    fn new(kind: EventKind, task: &Task) -> Self {
+        Event {
+            time: Local::now(),
+            kind,
+            task_id: task.id,
+            name: task.name.clone(),
+            base_priority: task.base_priority,
+            tags: task.tags.clone(),
+            project: task.project,
+            steps_waited: task.age,
+        }
+    }
+}
+
+/// A task the forecast expects to be worked on
+#[derive(Clone, PartialEq, Debug)]
+pub struct ForecastStep {
+    pub time: DateTime<Local>,
+    pub task_id: u64,
+    pub name: String,
+    pub priority: u8,
+}
+
+/// A deadline that will pass before the task is worked on
+#[derive(Clone, PartialEq, Debug)]
+pub struct MissedDeadline {
+    pub task_id: u64,
+    pub name: String,
+    pub deadline: DateTime<Local>,
+    /// When the task would first be worked on, if at all in the forecast
+    pub first_worked_on: Option<DateTime<Local>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Forecast {
+    pub steps: Vec<ForecastStep>,
+    pub missed_deadlines: Vec<MissedDeadline>,
+}
+
+/// Changes to make to several tasks at once. Anything left as `None`
+/// stays as it is.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Changes {
+    pub paused: Option<bool>,
+    /// Ignored for tasks with a deadline, which sets their priority
+    pub priority: Option<u8>,
+    pub deadline: Option<Option<DateTime<Local>>>,
+    pub tags: Option<Vec<String>>,
+    pub estimate: Option<Option<u32>>,
+    pub project: Option<Option<u64>>,
+    pub energy: Option<Option<Energy>>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Tasks {
     pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub deadline_policy: DeadlinePolicy,
+    #[serde(default)]
+    pub aging_policy: AgingPolicy,
+    #[serde(default)]
+    pub history: Vec<Event>,
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    /// What to share time between before going by priority
+    #[serde(default)]
+    pub fair_share: FairShare,
+    /// How much time each tag gets when time is shared between tags,
+    /// if not 1
+    #[serde(default)]
+    pub tag_weights: BTreeMap<String, u32>,
+    /// How much energy there is to give right now, which favours tasks
+    /// needing about that much
+    #[serde(default)]
+    pub energy: Option<Energy>,
+    #[serde(default)]
+    next_id: u64,
+    // Tells our own saves apart from someone else's
+    #[serde(default)]
+    saved_at: Option<DateTime<Local>>,
+    #[serde(skip, default = "memory_storage")]
+    storage: Rc<dyn Storage>,
+    #[serde(skip)]
+    undo: Option<Snapshot>,
+}
+
+// The list as it was before a change that can be undone
+#[derive(Clone)]
+struct Snapshot {
+    tasks: Vec<Task>,
+    history_len: usize,
+}
+
+fn memory_storage() -> Rc<dyn Storage> {
+    Rc::new(MemoryStorage::new())
 }
 
 impl Default for Tasks {
@@ -105,99 +564,671 @@ impl Default for Tasks {
 }
 
 impl Tasks {
+    /// An empty list kept in memory
     pub fn new() -> Self {
-        Tasks { tasks: Vec::new() }
+        Tasks {
+            tasks: Vec::new(),
+            deadline_policy: DeadlinePolicy::default(),
+            aging_policy: AgingPolicy::default(),
+            history: Vec::new(),
+            projects: Vec::new(),
+            fair_share: FairShare::Off,
+            tag_weights: BTreeMap::new(),
+            energy: None,
+            next_id: 1,
+            saved_at: None,
+            storage: memory_storage(),
+            undo: None,
+        }
     }
 
+    /// Load the list from the user's data directory
     pub fn load() -> Self {
-        let path = get_data_path();
-        let file = File::options().read(true).open(path).unwrap();
-        if file.metadata().unwrap().len() == 0 {
-            Tasks::new()
-        } else {
-            let mut ret: Tasks = serde_json::from_reader(file).unwrap();
-            let mut priority_modified = false;
-            for task in &mut ret.tasks {
-                if let Some(date) = task.deadline {
-                    let new_priority = Task::priority_from_deadline(date);
-                    if task.base_priority != new_priority {
-                        task.base_priority = new_priority;
-                        priority_modified = true
-                    }
-                    task.base_priority = Task::priority_from_deadline(date);
-                    task.priority = task.priority.min(task.base_priority)
-                }
+        Self::load_from(Rc::new(FileStorage::default()))
+    }
+
+    /// Load the list from `storage`, which it is saved back to after
+    /// every change
+    pub fn load_from(storage: Rc<dyn Storage>) -> Self {
+        let mut ret = match storage.load() {
+            Ok(saved) => {
+                let mut ret = saved.unwrap_or_default();
+                ret.storage = storage;
+                ret
             }
-            if priority_modified {
-                ret.tasks.sort();
+            // Keep what's there rather than saving over it
+            Err(error) => {
+                eprintln!(
+                    "Can't read the task list, so changes won't be saved: {}",
+                    error
+                );
+                Tasks::new()
             }
-            ret
+        };
+        ret.loaded();
+        ret
+    }
+
+    /// Pick up changes saved to storage by something else, like the CLI
+    /// or another front-end, so they don't get written over by our next
+    /// save. Returns whether anything changed.
+    pub fn reload(&mut self) -> bool {
+        // It may be halfway through being written, so try again later
+        let Ok(Some(mut saved)) = self.storage.load() else {
+            return false;
+        };
+        if saved.saved_at == self.saved_at {
+            return false;
         }
+        // Undoing would put back tasks from before those changes
+        saved.storage = self.storage.clone();
+        *self = saved;
+        self.loaded();
+        true
     }
 
-    fn save(&self) {
-        let path = get_data_path();
-        let file = File::options()
-            .write(true)
-            .truncate(true)
-            .open(path)
-            .unwrap();
-        serde_json::to_writer(file, self).unwrap();
+    fn loaded(&mut self) {
+        self.prune_history();
+        // Preferences used to let the working day end before it
+        // started, which leaves no time to work on anything
+        let now = Local::now();
+        let year = now + Duration::days(366);
+        if let Some(calendar) = &self.deadline_policy.calendar {
+            if calendar.working_minutes(&now, &year) == 0 {
+                self.deadline_policy.calendar = None;
+            }
+        }
+        // Quick add used to take "every 0 days"
+        for task in &mut self.tasks {
+            if let Some(recurrence) = task.recurrence.as_mut() {
+                recurrence.every = recurrence.every.max(1);
+            }
+        }
+        // Lists saved before tasks had ids
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].id == 0 {
+                self.tasks[i].id = self.new_id();
+            }
+        }
+        self.wake(Local::now());
+        self.update_deadlines(Local::now());
     }
 
-    pub fn step(&mut self) {
-        // Nothing to do if the list is empty
-        if self.tasks.is_empty() {
+    fn new_id(&mut self) -> u64 {
+        let max_id = self.tasks.iter().map(|x| x.id).max().unwrap_or(0);
+        self.next_id = self.next_id.max(max_id + 1);
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    fn record(&mut self, kind: EventKind, task: &Task) {
+        self.history.push(Event::new(kind, task));
+        self.prune_history();
+    }
+
+    fn prune_history(&mut self) {
+        let old = self.history.len().saturating_sub(HISTORY_LIMIT);
+        if old == 0 {
             return;
         }
+        self.history.drain(..old);
+        // Undoing still has to drop the events since the snapshot
+        if let Some(undo) = &mut self.undo {
+            undo.history_len = undo.history_len.saturating_sub(old);
+        }
+    }
+
+    /// Recalculate the priority of every task with a deadline, since
+    /// they get more urgent as time passes. Returns whether anything
+    /// changed.
+    pub fn update_deadlines(&mut self, now: DateTime<Local>) -> bool {
+        let mut priority_modified = false;
+        for task in &mut self.tasks {
+            if let Some(date) = task.deadline {
+                let new_priority = self.deadline_policy.priority(&date, &now);
+                if task.base_priority != new_priority {
+                    task.base_priority = new_priority;
+                    priority_modified = true
+                }
+                task.priority = task.priority.min(task.base_priority)
+            }
+        }
+        if priority_modified {
+            self.sort();
+        }
+        priority_modified
+    }
+
+    fn save(&mut self) {
+        self.saved_at = Some(Local::now());
+        self.storage.save(self);
+    }
+
+    // Put the tasks in the order they will be worked on
+    fn sort(&mut self) {
         self.tasks.sort();
-        let mut current_task = self.tasks.remove(0);
-        for task in self.tasks.iter_mut() {
-            task.age += 1
+        self.match_energy();
+        self.balance();
+    }
+
+    // Hold back tasks needing a different amount of energy than there
+    // is, by a priority level for each level of energy they're off by.
+    // Only the order changes, not their priorities.
+    fn match_energy(&mut self) {
+        let Some(energy) = self.energy else {
+            return;
+        };
+        let priority = |task: &Task| {
+            // Overdue tasks go first however much energy they need
+            if task.priority == 0 {
+                return 0;
+            }
+            let off = task.energy.map(|x| x.distance(&energy)).unwrap_or(0);
+            task.priority.saturating_add(off)
+        };
+        self.tasks.sort_by(|a, b| {
+            a.paused
+                .cmp(&b.paused)
+                .then(priority(a).cmp(&priority(b)))
+                .then(b.age.cmp(&a.age))
+        });
+    }
+
+    // The group a task or event is counted under when sharing time
+    fn group(&self, project: Option<u64>, tags: &[String]) -> Group {
+        match self.fair_share {
+            FairShare::Projects => project.map(Group::Project),
+            FairShare::Tags => tags.first().cloned().map(Group::Tag),
+            FairShare::Off => None,
+        }
+        .unwrap_or(Group::Other)
+    }
+
+    /// How much time a group gets compared to the others
+    pub fn weight(&self, group: &Group) -> u32 {
+        let weight = match group {
+            Group::Project(id) => self.project(*id).map(|x| x.weight),
+            Group::Tag(tag) => self.tag_weights.get(tag).copied(),
+            Group::Other => None,
+        };
+        weight.unwrap_or(1).max(1)
+    }
+
+    /// How many steps each group has been worked on for lately
+    pub fn service(&self) -> BTreeMap<Group, usize> {
+        let mut service = BTreeMap::new();
+        let steps = self
+            .history
+            .iter()
+            .rev()
+            .filter(|x| matches!(x.kind, EventKind::Stepped | EventKind::Finished))
+            .take(BALANCE_WINDOW);
+        for event in steps {
+            *service
+                .entry(self.group(event.project, &event.tags))
+                .or_default() += 1;
+        }
+        service
+    }
+
+    // Move the best task of the group that has had the least of its
+    // share lately to the head of the queue, if time is shared.
+    // Ties go to the most urgent group, then to the task that would
+    // have gone first anyway.
+    fn balance(&mut self) {
+        if self.fair_share == FairShare::Off {
+            return;
+        }
+        let now = Local::now();
+        // Overdue tasks go ahead of sharing time too
+        let head = self.tasks.first().filter(|x| !x.paused);
+        if self.deadline_policy.overdue_preempts
+            && head.is_some_and(|x| x.overdue_by(now).is_some())
+        {
+            return;
         }
-        let oldest_task = if self.tasks.len() == 1 {
-            &mut self.tasks[0]
+        let service = self.service();
+        let mut seen = Vec::new();
+        // (steps, weight, urgency, index) for the first task of each group
+        let mut candidates = Vec::new();
+        for (i, task) in self.tasks.iter().enumerate() {
+            if task.paused {
+                break;
+            }
+            let group = self.group(task.project, &task.tags);
+            if seen.contains(&group) {
+                continue;
+            }
+            let urgency = match &group {
+                Group::Project(id) => self
+                    .project(*id)
+                    .map(|x| x.urgency(&self.deadline_policy, now))
+                    .unwrap_or(task.priority),
+                _ => task.priority,
+            };
+            let steps = service.get(&group).copied().unwrap_or(0) as u64;
+            candidates.push((steps, self.weight(&group) as u64, urgency, i));
+            seen.push(group);
+        }
+        // Compare steps per weight without dividing
+        let best = candidates.into_iter().min_by(|a, b| {
+            (a.0 * b.1)
+                .cmp(&(b.0 * a.1))
+                .then(a.2.cmp(&b.2))
+                .then(a.3.cmp(&b.3))
+        });
+        if let Some((_, _, _, i)) = best.filter(|x| x.3 > 0) {
+            let task = self.tasks.remove(i);
+            self.tasks.insert(0, task);
+        }
+    }
+
+    /// The project with the given id
+    pub fn project(&self, id: u64) -> Option<&Project> {
+        self.projects.iter().find(|x| x.id == id)
+    }
+
+    /// Add a project, giving it an id. Returns the id.
+    pub fn add_project(&mut self, mut project: Project) -> u64 {
+        project.id = self.projects.iter().map(|x| x.id).max().unwrap_or(0) + 1;
+        let id = project.id;
+        self.projects.push(project);
+        self.save();
+        id
+    }
+
+    /// The id of the project called `name`, ignoring case, which is
+    /// added if there isn't one
+    pub fn project_named(&mut self, name: &str) -> u64 {
+        let found = self
+            .projects
+            .iter()
+            .find(|x| !x.archived && x.name.to_lowercase() == name.to_lowercase());
+        match found {
+            Some(project) => project.id,
+            None => {
+                let mut project = Project::new(String::from(name));
+                // A different colour from the last few
+                project.color = String::from(COLORS[self.projects.len() % COLORS.len()].1);
+                self.add_project(project)
+            }
+        }
+    }
+
+    /// Replace the project with the same id. Archiving it pauses its
+    /// tasks, and bringing it back resumes them.
+    pub fn update_project(&mut self, project: Project) {
+        let Some(i) = self.projects.iter().position(|x| x.id == project.id) else {
+            return;
+        };
+        if self.projects[i].archived != project.archived {
+            self.undo = None;
+            let mut toggled = Vec::new();
+            for task in self.tasks.iter_mut() {
+                if task.project == Some(project.id) && task.paused != project.archived {
+                    task.paused = project.archived;
+                    task.snoozed_until = None;
+                    toggled.push(task.clone());
+                }
+            }
+            let kind = if project.archived {
+                EventKind::Paused
+            } else {
+                EventKind::Resumed
+            };
+            for task in toggled {
+                self.record(kind, &task);
+            }
+        }
+        self.projects[i] = project;
+        self.sort();
+        self.save();
+    }
+
+    /// Remove a project, leaving its tasks without one
+    pub fn remove_project(&mut self, id: u64) {
+        self.projects.retain(|x| x.id != id);
+        for task in self.tasks.iter_mut().filter(|x| x.project == Some(id)) {
+            task.project = None;
+        }
+        self.sort();
+        self.save();
+    }
+
+    /// Set how much energy there is to give, or `None` to ignore how
+    /// much tasks need, and save
+    pub fn set_energy(&mut self, energy: Option<Energy>) {
+        self.energy = energy;
+        self.sort();
+        self.save();
+    }
+
+    /// Choose what to share time between, and save
+    pub fn set_fair_share(&mut self, fair_share: FairShare) {
+        self.fair_share = fair_share;
+        self.sort();
+        self.save();
+    }
+
+    /// Set how much time a tag gets when time is shared between tags,
+    /// and save
+    pub fn set_tag_weight(&mut self, tag: &str, weight: u32) {
+        if weight == 1 {
+            self.tag_weights.remove(tag);
         } else {
-            self.tasks
-                .iter_mut()
-                .filter(|x| !x.paused)
-                .max_by_key(|x| x.age)
-                .unwrap()
+            self.tag_weights.insert(String::from(tag), weight);
+        }
+        self.sort();
+        self.save();
+    }
+
+    /// Resume every snoozed task whose time has come, and return them
+    pub fn wake(&mut self, now: DateTime<Local>) -> Vec<Task> {
+        let woken = self.wake_up(now);
+        if !woken.is_empty() {
+            self.save();
+        }
+        woken
+    }
+
+    // The part of `wake` without saving
+    fn wake_up(&mut self, now: DateTime<Local>) -> Vec<Task> {
+        let mut woken = Vec::new();
+        for task in &mut self.tasks {
+            if task.snoozed_until.is_some_and(|x| x <= now) {
+                task.snoozed_until = None;
+                task.paused = false;
+                woken.push(task.clone());
+            }
+        }
+        for task in &woken {
+            self.record(EventKind::Resumed, task);
+        }
+        if !woken.is_empty() {
+            self.sort();
+        }
+        woken
+    }
+
+    /// Pause every task with one of the `ids` until `until`, when they
+    /// are resumed again
+    pub fn snooze(&mut self, ids: &[u64], until: DateTime<Local>) {
+        self.undo = None;
+        let mut snoozed = Vec::new();
+        for task in self.tasks.iter_mut().filter(|x| ids.contains(&x.id)) {
+            task.snoozed_until = Some(until);
+            if !task.paused {
+                task.paused = true;
+                snoozed.push(task.clone());
+            }
+        }
+        for task in snoozed {
+            self.record(EventKind::Paused, &task);
+        }
+        self.sort();
+        self.save();
+    }
+
+    /// The task with the given id
+    pub fn find(&self, id: u64) -> Option<&Task> {
+        self.tasks.iter().find(|x| x.id == id)
+    }
+
+    // Remember the list as it is, so the next change can be undone
+    fn checkpoint(&mut self) {
+        self.undo = Some(Snapshot {
+            tasks: self.tasks.clone(),
+            history_len: self.history.len(),
+        });
+    }
+
+    /// Whether the last change was removing or finishing tasks, or
+    /// changing several at once, which can be undone
+    pub fn can_undo(&self) -> bool {
+        self.undo.is_some()
+    }
+
+    /// Put back the tasks from before the last removal or finish.
+    /// Returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo.take() else {
+            return false;
         };
-        oldest_task.age();
-        current_task.reset();
-        self.tasks.push(current_task);
-        self.tasks.sort();
-        // Save after stepping
+        self.tasks = snapshot.tasks;
+        self.history.truncate(snapshot.history_len);
+        self.update_deadlines(Local::now());
+        self.save();
+        true
+    }
+
+    /// Work on the task with `id` instead of the one at the head of the
+    /// queue, as if it had just been run by `step`. Returns the task
+    /// that was promoted by aging, if its priority went up.
+    pub fn work_on(&mut self, id: u64) -> Option<Task> {
+        let index = self.tasks.iter().position(|x| x.id == id)?;
+        self.undo = None;
+        let promoted = self.run(index);
         self.save();
+        promoted
     }
 
-    pub fn step_and_finish(&mut self) {
+    /// Run the task at the head of the queue, unless every task is
+    /// paused. Returns the task that was promoted by aging, if its
+    /// priority went up.
+    pub fn step(&mut self) -> Option<Task> {
         // Nothing to do if the list is empty
         if self.tasks.is_empty() {
-            return;
+            return None;
         }
-        self.tasks.sort();
-        self.tasks.remove(0);
-        // Don't do anything else if we removed the last element
-        if self.tasks.is_empty() {
-            self.save();
-            return;
+        self.undo = None;
+        let promoted = self.advance();
+        // Save after stepping
+        self.save();
+        promoted
+    }
+
+    // The scheduling part of `step`, without saving
+    fn advance(&mut self) -> Option<Task> {
+        self.sort();
+        // Paused tasks sort last, so there is nothing to run
+        if self.tasks.first().is_none_or(|x| x.paused) {
+            return None;
         }
-        for task in self.tasks.iter_mut() {
-            task.age += 1
+        self.run(0)
+    }
+
+    // Treat the task at `index` as the one just worked on
+    fn run(&mut self, index: usize) -> Option<Task> {
+        let mut current_task = self.tasks.remove(index);
+        let promoted = self.age_waiting();
+        self.record(EventKind::Stepped, &current_task);
+        current_task.reset();
+        self.tasks.push(current_task);
+        self.sort();
+        promoted
+    }
+
+    // Age every task that is waiting to run, and promote the oldest
+    // ones. Returns the oldest promoted task if its priority went up.
+    fn age_waiting(&mut self) -> Option<Task> {
+        let policy = self.aging_policy;
+        for task in self.tasks.iter_mut().filter(|x| !x.paused) {
+            task.age = task.age.saturating_add(policy.increment);
         }
-        let oldest_task = self.tasks.iter_mut().max_by_key(|x| x.age).unwrap();
-        oldest_task.age();
-        self.tasks.sort();
+        let mut oldest: Vec<usize> = (0..self.tasks.len())
+            .filter(|x| !self.tasks[*x].paused && self.tasks[*x].age >= policy.threshold)
+            .collect();
+        // Oldest first, and the furthest down the list of the same age
+        oldest.sort_by_key(|x| std::cmp::Reverse((self.tasks[*x].age, *x)));
+        oldest.truncate(policy.boosted_per_step as usize);
+        let mut promoted = None;
+        for i in oldest {
+            let before = self.tasks[i].clone();
+            self.tasks[i].age(&policy);
+            if self.tasks[i].priority < before.priority {
+                self.record(EventKind::Promoted, &before);
+                promoted = promoted.or_else(|| Some(self.tasks[i].clone()));
+            }
+        }
+        promoted
+    }
+
+    /// Change how tasks are aged, and save
+    pub fn set_aging_policy(&mut self, policy: AgingPolicy) {
+        self.aging_policy = policy;
+        self.save();
+    }
+
+    /// Change how deadlines turn into priorities, and save. Tasks with
+    /// a deadline start again from their new priority.
+    pub fn set_deadline_policy(&mut self, policy: DeadlinePolicy) {
+        self.deadline_policy = policy;
+        let now = Local::now();
+        for task in &mut self.tasks {
+            if let Some(date) = task.deadline {
+                task.base_priority = self.deadline_policy.priority(&date, &now);
+                task.priority = task.base_priority;
+            }
+        }
+        self.sort();
+        self.save();
+    }
+
+    /// Forecast what the next `n_steps` steps will work on, assuming a
+    /// step every `step_length` from `now`. Nothing is saved.
+    pub fn simulate_from(
+        &self,
+        n_steps: usize,
+        now: DateTime<Local>,
+        step_length: Duration,
+    ) -> Forecast {
+        let mut copy = self.clone();
+        // Only the latest steps are needed, for sharing time
+        copy.history
+            .retain(|x| matches!(x.kind, EventKind::Stepped | EventKind::Finished));
+        let old = copy.history.len().saturating_sub(BALANCE_WINDOW);
+        copy.history.drain(..old);
+        let mut forecast = Forecast::default();
+        let mut time = now;
+        for _ in 0..n_steps {
+            copy.wake_up(time);
+            copy.update_deadlines(time);
+            copy.sort();
+            match copy.tasks.first() {
+                Some(task) if !task.paused => forecast.steps.push(ForecastStep {
+                    time,
+                    task_id: task.id,
+                    name: task.name.clone(),
+                    priority: task.priority,
+                }),
+                // Nothing to do until a snoozed task wakes up
+                _ if copy.tasks.iter().any(|x| x.snoozed_until.is_some()) => {
+                    time += step_length;
+                    continue;
+                }
+                // Everything left is paused
+                _ => break,
+            }
+            copy.advance();
+            time += step_length;
+        }
+        let waiting = self
+            .tasks
+            .iter()
+            .filter(|x| !x.paused || x.snoozed_until.is_some());
+        for task in waiting {
+            let Some(deadline) = task.deadline else {
+                continue;
+            };
+            let first_worked_on = forecast
+                .steps
+                .iter()
+                .find(|x| x.task_id == task.id)
+                .map(|x| x.time);
+            let missed = match first_worked_on {
+                Some(first) => first >= deadline,
+                None => deadline < time,
+            };
+            if missed {
+                forecast.missed_deadlines.push(MissedDeadline {
+                    task_id: task.id,
+                    name: task.name.clone(),
+                    deadline,
+                    first_worked_on,
+                });
+            }
+        }
+        forecast
+    }
+
+    /// Forecast the next `n_steps` steps from now, with the default
+    /// length of a work session for each step
+    pub fn simulate(&self, n_steps: usize) -> Forecast {
+        self.simulate_from(
+            n_steps,
+            Local::now(),
+            Duration::minutes(DEFAULT_QUANTUM_MINUTES as i64),
+        )
+    }
+
+    /// Finish the task at the head of the queue, unless every task is
+    /// paused. Returns the task that was promoted by aging, if its
+    /// priority went up.
+    pub fn step_and_finish(&mut self) -> Option<Task> {
+        self.sort();
+        let id = self.tasks.first().filter(|x| !x.paused)?.id;
+        self.finish(id)
+    }
+
+    /// Finish the task with `id`, unless it's paused or gone. Returns
+    /// the task that was promoted by aging, if its priority went up.
+    pub fn finish(&mut self, id: u64) -> Option<Task> {
+        // Nothing to finish, or to undo, otherwise
+        let index = self.tasks.iter().position(|x| x.id == id && !x.paused)?;
+        self.checkpoint();
+        let promoted = self.finish_at(index);
         // Save after stepping
         self.save();
+        promoted
+    }
+
+    // The scheduling part of `finish`, without saving
+    fn finish_at(&mut self, index: usize) -> Option<Task> {
+        let finished = self.tasks.remove(index);
+        self.record(EventKind::Finished, &finished);
+        let next = self.next_occurrence(finished);
+        let promoted = self.age_waiting();
+        self.tasks.extend(next);
+        self.sort();
+        promoted
+    }
+
+    // The next copy of a recurring task that was just finished
+    fn next_occurrence(&self, mut task: Task) -> Option<Task> {
+        let recurrence = task.recurrence?;
+        let now = Local::now();
+        let mut deadline = recurrence.next(task.deadline.unwrap_or(now));
+        // Skip the ones missed while it was overdue, stopping if the date
+        // can't go any further
+        while deadline <= now {
+            let next = recurrence.next(deadline);
+            if next <= deadline {
+                break;
+            }
+            deadline = next;
+        }
+        task.deadline = Some(deadline);
+        task.base_priority = self.deadline_policy.priority(&deadline, &now);
+        task.reset();
+        Some(task)
     }
 
     pub fn remove(&mut self, task: Task) {
         for (i, i_task) in self.tasks.iter().enumerate() {
             if task == *i_task {
-                self.tasks.remove(i);
+                self.checkpoint();
+                let removed = self.tasks.remove(i);
+                self.record(EventKind::Removed, &removed);
                 self.save();
                 return;
             }
@@ -208,20 +1239,201 @@ impl Tasks {
         }
     }
 
-    pub fn add(&mut self, task: Task) {
+    pub fn add(&mut self, mut task: Task) {
+        // Use this list's policy rather than the default one
+        if let Some(date) = task.deadline {
+            task.base_priority = self.deadline_policy.priority(&date, &Local::now());
+            task.priority = task.base_priority;
+        }
+        self.undo = None;
+        task.id = self.new_id();
+        self.record(EventKind::Added, &task);
         self.tasks.push(task);
-        self.tasks.sort();
+        self.sort();
         // Save after adding
         self.save();
     }
 
+    /// Remove every task with one of the `ids`, as one change that can
+    /// be undone
+    pub fn remove_all(&mut self, ids: &[u64]) {
+        if !self.tasks.iter().any(|x| ids.contains(&x.id)) {
+            return;
+        }
+        self.checkpoint();
+        let (removed, kept) = self.tasks.drain(..).partition(|x| ids.contains(&x.id));
+        self.tasks = kept;
+        for task in removed {
+            self.record(EventKind::Removed, &task);
+        }
+        self.save();
+    }
+
+    /// Raise tasks by a priority level, as one change that can be undone.
+    /// Tasks with a deadline get their priority from it, so they are
+    /// left alone. Returns how many were changed.
+    pub fn raise_priority(&mut self, ids: &[u64]) -> usize {
+        self.shift_priority(ids, -1)
+    }
+
+    /// Lower tasks by a priority level, like `raise_priority`
+    pub fn lower_priority(&mut self, ids: &[u64]) -> usize {
+        self.shift_priority(ids, 1)
+    }
+
+    // Move the base priority of tasks by `by` levels, keeping whatever
+    // they have been promoted by while waiting
+    fn shift_priority(&mut self, ids: &[u64], by: i16) -> usize {
+        let shifted = |task: &Task| (task.base_priority as i16 + by).clamp(1, u8::MAX as i16) as u8;
+        let changing: Vec<usize> = (0..self.tasks.len())
+            .filter(|x| {
+                let task = &self.tasks[*x];
+                ids.contains(&task.id)
+                    && task.deadline.is_none()
+                    && shifted(task) != task.base_priority
+            })
+            .collect();
+        if changing.is_empty() {
+            return 0;
+        }
+        self.checkpoint();
+        for i in &changing {
+            let task = &mut self.tasks[*i];
+            let promoted = task.base_priority.saturating_sub(task.priority);
+            task.base_priority = shifted(task);
+            task.priority = task.base_priority.saturating_sub(promoted).max(1);
+        }
+        self.sort();
+        self.save();
+        changing.len()
+    }
+
+    /// Give a task a new name, as a change that can be undone. Returns
+    /// whether it was renamed.
+    pub fn rename(&mut self, id: u64, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.find(id).is_none_or(|x| x.name == name) {
+            return false;
+        }
+        self.checkpoint();
+        if let Some(task) = self.tasks.iter_mut().find(|x| x.id == id) {
+            task.name = String::from(name);
+        }
+        self.save();
+        true
+    }
+
+    /// Move a task to `index` in the queue by hand. It takes the
+    /// priority and age of the task it was moved next to, so it stays
+    /// there when the list is sorted again. Tasks with a deadline keep
+    /// the priority it gives them, so they can only be moved up for
+    /// good, and nothing but an overdue task can take the place of one.
+    /// Paused tasks can't be moved. Returns whether it was moved.
+    ///
+    /// Only priority and age are changed, so with energy matching or
+    /// fair share on, those still have their say over where it ends up.
+    pub fn move_task(&mut self, id: u64, index: usize) -> bool {
+        let Some(from) = self.tasks.iter().position(|x| x.id == id) else {
+            return false;
+        };
+        let unpaused = self.tasks.iter().filter(|x| !x.paused).count();
+        if self.tasks[from].paused || index >= unpaused || index == from {
+            return false;
+        }
+        self.checkpoint();
+        let mut task = self.tasks.remove(from);
+        // The task it was moved past, which is now next to it
+        let neighbour = if index > from {
+            &self.tasks[index - 1]
+        } else {
+            &self.tasks[index]
+        };
+        task.age = neighbour.age;
+        // Priority 0 is kept for overdue tasks
+        if task.deadline.is_some() {
+            task.priority = neighbour.priority.max(1).min(task.base_priority);
+        } else {
+            task.priority = neighbour.priority.max(1);
+            task.base_priority = neighbour.base_priority.max(1);
+        }
+        // Sorting keeps tasks that compare equal in the order they're in
+        self.tasks.insert(index, task);
+        self.sort();
+        self.save();
+        true
+    }
+
+    /// Make the same changes to every task with one of the `ids`, as
+    /// one change that can be undone
+    pub fn change(&mut self, ids: &[u64], changes: &Changes) {
+        if !self.tasks.iter().any(|x| ids.contains(&x.id)) {
+            return;
+        }
+        self.checkpoint();
+        let now = Local::now();
+        let mut events = Vec::new();
+        for task in self.tasks.iter_mut().filter(|x| ids.contains(&x.id)) {
+            if let Some(paused) = changes.paused.filter(|x| *x != task.paused) {
+                task.paused = paused;
+                task.snoozed_until = None;
+                let kind = if paused {
+                    EventKind::Paused
+                } else {
+                    EventKind::Resumed
+                };
+                events.push((kind, task.clone()));
+            }
+            if let Some(tags) = &changes.tags {
+                task.tags = tags.clone();
+            }
+            if let Some(deadline) = changes.deadline {
+                task.deadline = deadline;
+            }
+            if let Some(estimate) = changes.estimate {
+                task.estimate = estimate;
+            }
+            if let Some(project) = changes.project {
+                task.project = project;
+            }
+            if let Some(energy) = changes.energy {
+                task.energy = energy;
+            }
+            if let Some(priority) = changes.priority {
+                task.base_priority = priority;
+            }
+            if let Some(date) = task.deadline {
+                task.base_priority = self.deadline_policy.priority(&date, &now);
+            }
+            if changes.priority.is_some() || changes.deadline.is_some() {
+                task.priority = task.base_priority;
+            }
+        }
+        for (kind, task) in events {
+            self.record(kind, &task);
+        }
+        self.sort();
+        self.save();
+    }
+
     pub fn toggle_pause(&mut self, task: &Task) {
+        self.undo = None;
+        let mut toggled = Vec::new();
         for t in &mut self.tasks {
             if *task == *t {
-                t.paused = !t.paused
+                t.paused = !t.paused;
+                t.snoozed_until = None;
+                toggled.push(t.clone());
             }
         }
-        self.tasks.sort();
+        for t in toggled {
+            let kind = if t.paused {
+                EventKind::Paused
+            } else {
+                EventKind::Resumed
+            };
+            self.record(kind, &t);
+        }
+        self.sort();
         self.save();
     }
 }
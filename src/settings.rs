@@ -0,0 +1,121 @@
+use std::cell::{Cell, Ref, RefCell};
+use std::rc::Rc;
+
+use gtk::gio;
+use gtk::prelude::*;
+use nasin::config::{is_valid_format, Config};
+
+const SCHEMA_ID: &str = "me.jonot.Nasin";
+
+type Callback = Box<dyn Fn(&Config)>;
+
+/// The shared config file, mirrored into GSettings when the schema is
+/// installed so it can be changed with the usual GNOME tools too
+pub struct Settings {
+    config: RefCell<Config>,
+    gsettings: Option<gio::Settings>,
+    // Set while we write to GSettings, so we don't read half of it back
+    writing: Cell<bool>,
+    callbacks: RefCell<Vec<Callback>>,
+}
+
+fn write_gsettings(gsettings: &gio::Settings, config: &Config) {
+    let data_dir = config
+        .data_dir
+        .as_ref()
+        .map(|x| x.display().to_string())
+        .unwrap_or_default();
+    let _ = gsettings.set_string("data-dir", &data_dir);
+    let _ = gsettings.set_string("date-format", &config.date_format);
+    let _ = gsettings.set_string("time-format", &config.time_format);
+    let _ = gsettings.set_int("default-priority", config.default_priority as i32);
+    let _ = gsettings.set_boolean("confirm-remove", config.confirm.remove);
+    let _ = gsettings.set_boolean("confirm-finish", config.confirm.finish);
+    let _ = gsettings.set_boolean("confirm-bulk", config.confirm.bulk);
+}
+
+fn read_gsettings(gsettings: &gio::Settings, config: &mut Config) {
+    let data_dir = gsettings.string("data-dir");
+    config.data_dir = (!data_dir.is_empty()).then(|| data_dir.as_str().into());
+    for (key, format) in [
+        ("date-format", &mut config.date_format),
+        ("time-format", &mut config.time_format),
+    ] {
+        let value = gsettings.string(key);
+        if is_valid_format(&value) {
+            *format = value.to_string();
+        }
+    }
+    config.default_priority = gsettings.int("default-priority").clamp(1, 255) as u8;
+    config.confirm.remove = gsettings.boolean("confirm-remove");
+    config.confirm.finish = gsettings.boolean("confirm-finish");
+    config.confirm.bulk = gsettings.boolean("confirm-bulk");
+}
+
+impl Settings {
+    pub fn load() -> Rc<Self> {
+        let gsettings = gio::SettingsSchemaSource::default()
+            .and_then(|x| x.lookup(SCHEMA_ID, true))
+            .map(|_| gio::Settings::new(SCHEMA_ID));
+        let settings = Rc::new(Settings {
+            config: RefCell::new(Config::load()),
+            gsettings,
+            writing: Cell::new(false),
+            callbacks: RefCell::new(Vec::new()),
+        });
+        if let Some(gsettings) = &settings.gsettings {
+            // The config file wins, since the TUI may have changed it
+            settings.write();
+            let weak = Rc::downgrade(&settings);
+            gsettings.connect_changed(None, move |gsettings, _| {
+                let Some(settings) = weak.upgrade() else {
+                    return;
+                };
+                if settings.writing.get() {
+                    return;
+                }
+                let mut config = settings.config().clone();
+                read_gsettings(gsettings, &mut config);
+                if config != *settings.config() {
+                    settings.set(config);
+                }
+            });
+        }
+        settings
+    }
+
+    pub fn config(&self) -> Ref<'_, Config> {
+        self.config.borrow()
+    }
+
+    /// Change the config, saving it everywhere
+    pub fn update(&self, f: impl FnOnce(&mut Config)) {
+        let mut config = self.config().clone();
+        f(&mut config);
+        if config != *self.config() {
+            self.set(config);
+            self.write();
+        }
+    }
+
+    /// Call `f` whenever the config changes
+    pub fn connect_changed(&self, f: impl Fn(&Config) + 'static) {
+        self.callbacks.borrow_mut().push(Box::new(f));
+    }
+
+    fn set(&self, config: Config) {
+        config.save();
+        self.config.replace(config);
+        for callback in self.callbacks.borrow().iter() {
+            callback(&self.config());
+        }
+    }
+
+    fn write(&self) {
+        if let Some(gsettings) = &self.gsettings {
+            self.writing.set(true);
+            write_gsettings(gsettings, &self.config());
+            self.writing.set(false);
+        }
+    }
+}
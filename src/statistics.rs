@@ -0,0 +1,138 @@
+use chrono::Local;
+use gtk::{cairo, glib, DrawingArea, Orientation, ScrolledWindow};
+use libadwaita::prelude::*;
+use libadwaita::{ActionRow, ApplicationWindow, HeaderBar, PreferencesGroup};
+use nasin::scheduler::{format_duration, Tasks};
+use nasin::stats::Stats;
+
+// Colour of the bars, the same as the default GNOME accent colour
+const BAR_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+fn draw_bar_chart(cr: &cairo::Context, width: i32, height: i32, stats: &Stats) {
+    let days = &stats.completions_per_day;
+    if days.is_empty() {
+        return;
+    }
+    let label_height = 18.0;
+    let max = days.iter().map(|x| x.1).max().unwrap_or(0).max(1) as f64;
+    let slot = width as f64 / days.len() as f64;
+    let chart_height = height as f64 - label_height * 2.0;
+    cr.set_font_size(11.0);
+    for (i, (date, count)) in days.iter().enumerate() {
+        let bar_height = chart_height * (*count as f64 / max);
+        let x = i as f64 * slot + slot * 0.15;
+        let y = label_height + chart_height - bar_height;
+        cr.set_source_rgb(BAR_COLOR.0, BAR_COLOR.1, BAR_COLOR.2);
+        cr.rectangle(x, y, slot * 0.7, bar_height);
+        let _ = cr.fill();
+
+        cr.set_source_rgb(0.5, 0.5, 0.5);
+        if *count > 0 {
+            cr.move_to(x, y - 4.0);
+            let _ = cr.show_text(&count.to_string());
+        }
+        cr.move_to(x, height as f64 - 4.0);
+        let _ = cr.show_text(&date.format("%d").to_string());
+    }
+}
+
+fn row(title: &str, subtitle: &str) -> ActionRow {
+    ActionRow::builder()
+        .title(glib::markup_escape_text(title))
+        .subtitle(glib::markup_escape_text(subtitle))
+        .build()
+}
+
+/// Show a window with statistics worked out from the scheduler's history
+pub fn show_statistics(parent: &ApplicationWindow, tasks: &Tasks) {
+    let stats = Stats::compute(&tasks.history, Local::now(), 14);
+
+    let chart = DrawingArea::builder()
+        .content_height(180)
+        .hexpand(true)
+        .build();
+    let chart_stats = stats.clone();
+    chart
+        .set_draw_func(move |_, cr, width, height| draw_bar_chart(cr, width, height, &chart_stats));
+    let chart_group = PreferencesGroup::builder()
+        .title("Finished per Day")
+        .description(format!(
+            "{} tasks finished in the last two weeks",
+            stats.total_completions()
+        ))
+        .build();
+    chart_group.add(&chart);
+
+    let wait_group = PreferencesGroup::builder().title("Waiting").build();
+    let average_wait = stats
+        .average_wait
+        .map(format_duration)
+        .unwrap_or_else(|| String::from("Nothing worked on yet"));
+    wait_group.add(&row("Average wait before being worked on", &average_wait));
+
+    let tag_group = PreferencesGroup::builder().title("Time per Tag").build();
+    for (tag, time) in &stats.time_per_tag {
+        tag_group.add(&row(&format!("#{}", tag), &format_duration(*time)));
+    }
+    let priority_group = PreferencesGroup::builder()
+        .title("Time per Priority")
+        .build();
+    for (priority, time) in &stats.time_per_priority {
+        priority_group.add(&row(
+            &format!("Priority {}", priority),
+            &format_duration(*time),
+        ));
+    }
+
+    let starvation_group = PreferencesGroup::builder()
+        .title("Starvation")
+        .description("How long tasks at each priority wait before being worked on")
+        .build();
+    for level in &stats.starvation {
+        starvation_group.add(&row(
+            &format!("Priority {}", level.priority),
+            &format!(
+                "Worked on {} times, waited {:.1} steps on average and {} at most, promoted {} times",
+                level.runs, level.average_steps_waited, level.max_steps_waited, level.promotions
+            ),
+        ));
+    }
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(24)
+        .margin_top(24)
+        .margin_bottom(24)
+        .margin_start(24)
+        .margin_end(24)
+        .build();
+    content.append(&chart_group);
+    content.append(&wait_group);
+    if !stats.time_per_tag.is_empty() {
+        content.append(&tag_group);
+    }
+    if !stats.time_per_priority.is_empty() {
+        content.append(&priority_group);
+    }
+    if !stats.starvation.is_empty() {
+        content.append(&starvation_group);
+    }
+
+    let holder_box = gtk::Box::new(Orientation::Vertical, 0);
+    holder_box.append(&HeaderBar::new());
+    holder_box.append(
+        &ScrolledWindow::builder()
+            .child(&content)
+            .vexpand(true)
+            .min_content_height(500)
+            .build(),
+    );
+
+    let window = ApplicationWindow::builder()
+        .title("Statistics")
+        .transient_for(parent)
+        .default_width(480)
+        .content(&holder_box)
+        .build();
+    window.present();
+}
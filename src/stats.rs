@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Days, Duration, Local, NaiveDate};
+
+use crate::scheduler::{Event, EventKind};
+
+/// Gaps between steps longer than this are assumed to be breaks, not
+/// time spent on the task
+const MAX_WORK_SESSION: i64 = 4 * 60;
+
+/// How many of the latest runs `recent_waits` covers
+pub const RECENT_RUNS: usize = 100;
+
+/// How well a priority level is being served by the scheduler
+#[derive(Clone, PartialEq, Debug)]
+pub struct Starvation {
+    pub priority: u8,
+    /// Times a task at this level was worked on or finished
+    pub runs: u32,
+    /// Times a task at this level had to be promoted by aging
+    pub promotions: u32,
+    pub average_steps_waited: f64,
+    pub max_steps_waited: u32,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Stats {
+    /// Finished tasks for each of the last few days, oldest first
+    pub completions_per_day: Vec<(NaiveDate, u32)>,
+    /// Average time between a task being added or worked on and the
+    /// next time it is worked on
+    pub average_wait: Option<Duration>,
+    pub time_per_tag: Vec<(String, Duration)>,
+    pub time_per_priority: Vec<(u8, Duration)>,
+    pub starvation: Vec<Starvation>,
+    /// Steps waited by each task as it was worked on, for the last
+    /// `RECENT_RUNS` runs, oldest first
+    pub recent_waits: Vec<u64>,
+}
+
+fn is_run(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Stepped | EventKind::Finished)
+}
+
+impl Stats {
+    /// Work out statistics from the scheduler's history, with daily
+    /// completions for the `days` days up to `now`
+    pub fn compute(history: &[Event], now: DateTime<Local>, days: u32) -> Self {
+        let mut stats = Stats::default();
+
+        let today = now.date_naive();
+        let mut per_day: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+        for i in 0..days {
+            per_day.insert(today - Days::new(i as u64), 0);
+        }
+        for event in history.iter().filter(|x| x.kind == EventKind::Finished) {
+            if let Some(count) = per_day.get_mut(&event.time.date_naive()) {
+                *count += 1;
+            }
+        }
+        stats.completions_per_day = per_day.into_iter().collect();
+
+        // Time between being added or worked on, and being worked on again
+        let mut waiting_since: HashMap<u64, DateTime<Local>> = HashMap::new();
+        let mut waits: Vec<Duration> = Vec::new();
+        for event in history {
+            match event.kind {
+                EventKind::Added => {
+                    waiting_since.insert(event.task_id, event.time);
+                }
+                EventKind::Stepped | EventKind::Finished => {
+                    if let Some(since) = waiting_since.remove(&event.task_id) {
+                        waits.push(event.time - since);
+                    }
+                    if event.kind == EventKind::Stepped {
+                        waiting_since.insert(event.task_id, event.time);
+                    }
+                }
+                EventKind::Removed => {
+                    waiting_since.remove(&event.task_id);
+                }
+                _ => {}
+            }
+        }
+        if !waits.is_empty() {
+            let total: Duration = waits.iter().copied().sum();
+            stats.average_wait = Some(total / waits.len() as i32);
+        }
+
+        // Time spent on a task is the time since the previous step
+        let mut per_tag: BTreeMap<String, Duration> = BTreeMap::new();
+        let mut per_priority: BTreeMap<u8, Duration> = BTreeMap::new();
+        let mut previous: Option<DateTime<Local>> = None;
+        for event in history.iter().filter(|x| is_run(x)) {
+            if let Some(previous) = previous {
+                let spent = event.time - previous;
+                if spent <= Duration::minutes(MAX_WORK_SESSION) {
+                    for tag in &event.tags {
+                        *per_tag.entry(tag.clone()).or_insert(Duration::zero()) += spent;
+                    }
+                    *per_priority
+                        .entry(event.base_priority)
+                        .or_insert(Duration::zero()) += spent;
+                }
+            }
+            previous = Some(event.time);
+        }
+        stats.time_per_tag = per_tag.into_iter().collect();
+        stats.time_per_tag.sort_by_key(|x| std::cmp::Reverse(x.1));
+        stats.time_per_priority = per_priority.into_iter().collect();
+
+        let mut levels: BTreeMap<u8, (u32, u32, u64, u32)> = BTreeMap::new();
+        for event in history {
+            let level = levels.entry(event.base_priority).or_default();
+            match event.kind {
+                EventKind::Stepped | EventKind::Finished => {
+                    level.0 += 1;
+                    level.2 += event.steps_waited as u64;
+                    level.3 = level.3.max(event.steps_waited);
+                }
+                EventKind::Promoted => level.1 += 1,
+                _ => {}
+            }
+        }
+        stats.starvation = levels
+            .into_iter()
+            .filter(|(_, (runs, promotions, _, _))| *runs > 0 || *promotions > 0)
+            .map(|(priority, (runs, promotions, total, max))| Starvation {
+                priority,
+                runs,
+                promotions,
+                average_steps_waited: if runs == 0 {
+                    0.0
+                } else {
+                    total as f64 / runs as f64
+                },
+                max_steps_waited: max,
+            })
+            .collect();
+
+        let mut recent: Vec<u64> = history
+            .iter()
+            .rev()
+            .filter(|x| is_run(x))
+            .take(RECENT_RUNS)
+            .map(|x| x.steps_waited as u64)
+            .collect();
+        recent.reverse();
+        stats.recent_waits = recent;
+        stats
+    }
+
+    /// Finished tasks over the whole period
+    pub fn total_completions(&self) -> u32 {
+        self.completions_per_day.iter().map(|x| x.1).sum()
+    }
+}
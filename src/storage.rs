@@ -0,0 +1,102 @@
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+use crate::config::Config;
+use crate::scheduler::Tasks;
+
+pub(crate) fn get_data_dir() -> PathBuf {
+    let path = Config::load().data_dir();
+    if !path.exists() {
+        std::fs::create_dir_all(path.clone()).unwrap();
+    }
+    path
+}
+
+/// Somewhere a task list is kept between runs
+pub trait Storage {
+    /// The saved list, or `None` if nothing has been saved yet
+    fn load(&self) -> io::Result<Option<Tasks>>;
+    fn save(&self, tasks: &Tasks);
+}
+
+/// Keeps the list as JSON in a file
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: PathBuf) -> Self {
+        FileStorage { path }
+    }
+}
+
+impl Default for FileStorage {
+    /// `tasks.json` in the configured data directory
+    fn default() -> Self {
+        let mut path = get_data_dir();
+        path.push("tasks");
+        path.set_extension("json");
+        FileStorage::new(path)
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self) -> io::Result<Option<Tasks>> {
+        let file = match File::options().read(true).open(&self.path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        if file.metadata()?.len() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_reader(file)?))
+    }
+
+    fn save(&self, tasks: &Tasks) {
+        let file = File::options()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)
+            .unwrap();
+        serde_json::to_writer(file, tasks).unwrap();
+    }
+}
+
+/// Keeps the list in memory, for tests and lists that shouldn't be
+/// saved anywhere
+#[derive(Default)]
+pub struct MemoryStorage {
+    saved: RefCell<Option<String>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The list as it was last saved, as JSON
+    pub fn saved(&self) -> Option<String> {
+        self.saved.borrow().clone()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self) -> io::Result<Option<Tasks>> {
+        let saved = self.saved.borrow();
+        let tasks = saved
+            .as_ref()
+            .map(|x| serde_json::from_str(x))
+            .transpose()?;
+        Ok(tasks)
+    }
+
+    fn save(&self, tasks: &Tasks) {
+        *self.saved.borrow_mut() = Some(serde_json::to_string(tasks).unwrap());
+    }
+}
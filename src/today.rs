@@ -0,0 +1,41 @@
+use chrono::{Duration, Local};
+use gtk::glib;
+use libadwaita::prelude::*;
+use libadwaita::{ActionRow, PreferencesGroup};
+use nasin::agenda::Agenda;
+use nasin::config::Config;
+use nasin::scheduler::Tasks;
+
+/// Fill `page` with the plan for the rest of today, replacing whatever
+/// was there
+pub fn build_today(page: &gtk::Box, tasks: &Tasks, config: &Config, quantum_minutes: u32) {
+    while let Some(child) = page.first_child() {
+        page.remove(&child);
+    }
+    let agenda = Agenda::plan(
+        tasks,
+        Local::now(),
+        config.today.steps(quantum_minutes),
+        Duration::minutes(quantum_minutes as i64),
+    );
+    let sections = agenda.sections(config);
+    if sections.is_empty() {
+        let group = PreferencesGroup::new();
+        group.add(&ActionRow::builder().title("Nothing to do today").build());
+        page.append(&group);
+    }
+    for (title, entries) in sections {
+        let group = PreferencesGroup::builder().title(title).build();
+        for entry in entries {
+            let row = ActionRow::builder()
+                .title(glib::markup_escape_text(&entry.name))
+                .subtitle(entry.detail)
+                .build();
+            if title == "Overdue" {
+                row.add_css_class("error");
+            }
+            group.add(&row);
+        }
+        page.append(&group);
+    }
+}
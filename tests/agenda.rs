@@ -0,0 +1,80 @@
+use chrono::{DateTime, Duration, Local};
+use nasin::agenda::Agenda;
+use nasin::config::{Config, DayPlan};
+use nasin::scheduler::{Recurrence, RecurrenceUnit, Task, Tasks};
+
+mod common;
+use common::{local, names};
+
+fn at(h: u32, min: u32) -> DateTime<Local> {
+    local(2025, 3, 12, h, min)
+}
+
+fn day() -> Tasks {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(String::from("Pay rent"), 1, Some(at(9, 0))));
+    tasks.add(Task::new(String::from("Send report"), 1, Some(at(17, 0))));
+    tasks.add(Task::new(
+        String::from("Book flights"),
+        1,
+        Some(at(17, 0) + Duration::days(2)),
+    ));
+    tasks.add(Task::new(String::from("Read"), 1, None));
+    let mut plants = Task::new(String::from("Water plants"), 1, Some(at(18, 0)));
+    plants.recurrence = Some(Recurrence {
+        every: 1,
+        unit: RecurrenceUnit::Day,
+    });
+    tasks.add(plants);
+    tasks
+}
+
+#[test]
+fn sorts_todays_deadlines() {
+    let agenda = Agenda::plan(&day(), at(12, 0), 4, Duration::minutes(25));
+    assert_eq!(names(&agenda.overdue), vec!["Pay rent"]);
+    assert_eq!(names(&agenda.due_today), vec!["Send report"]);
+    assert_eq!(names(&agenda.chores), vec!["Water plants"]);
+    assert_eq!(agenda.planned.len(), 4);
+    assert_eq!(agenda.planned[0].time, at(12, 0));
+}
+
+#[test]
+fn plan_ends_with_the_day() {
+    let agenda = Agenda::plan(&day(), at(22, 0), 20, Duration::minutes(25));
+    assert_eq!(agenda.planned.len(), 5);
+    assert!(agenda.planned.iter().all(|x| x.time < at(23, 59)));
+}
+
+#[test]
+fn empty_list_has_nothing_to_do() {
+    let agenda = Agenda::plan(&Tasks::new(), at(12, 0), 4, Duration::minutes(25));
+    let config = Config::default();
+    assert!(agenda.sections(&config).is_empty());
+    assert_eq!(
+        agenda.to_text(&config, false),
+        "Today, 2025-03-12\n\nNothing to do today\n"
+    );
+}
+
+#[test]
+fn markdown_has_a_checklist() {
+    let agenda = Agenda::plan(&day(), at(12, 0), 1, Duration::minutes(25));
+    let text = agenda.to_text(&Config::default(), true);
+    assert!(text.starts_with("# Today, 2025-03-12\n"));
+    assert!(text.contains("## Overdue\n\n- [ ] Pay rent (due 2025-03-12 09:00)\n"));
+    assert!(text.contains("## Chores\n\n- [ ] Water plants (due 2025-03-12 18:00)\n"));
+    assert!(text.contains("## Plan\n\n- [ ] "));
+}
+
+#[test]
+fn day_plan_fills_the_hours() {
+    assert_eq!(DayPlan::default().steps(25), 14);
+    let plan = DayPlan {
+        hours: 6,
+        steps: Some(3),
+    };
+    assert_eq!(plan.steps(25), 3);
+    let config = Config::from_toml("[today]\nhours = 2\n").unwrap();
+    assert_eq!(config.today.steps(30), 4);
+}
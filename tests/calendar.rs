@@ -0,0 +1,144 @@
+use std::rc::Rc;
+
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone};
+use nasin::calendar::Calendar;
+use nasin::scheduler::{DeadlinePolicy, Tasks};
+use nasin::storage::MemoryStorage;
+
+// 10 March 2025 is a Monday
+fn at(d: u32, h: u32, min: u32) -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(3600)
+        .unwrap()
+        .with_ymd_and_hms(2025, 3, d, h, min, 0)
+        .unwrap()
+}
+
+fn working_hours(hours_per_level: u32) -> DeadlinePolicy {
+    DeadlinePolicy {
+        hours_per_level,
+        calendar: Some(Calendar::default()),
+        ..DeadlinePolicy::default()
+    }
+}
+
+#[test]
+fn counts_time_within_working_hours() {
+    let calendar = Calendar::default();
+    assert_eq!(
+        calendar.working_minutes(&at(10, 10, 0), &at(10, 12, 0)),
+        120
+    );
+    assert_eq!(calendar.working_minutes(&at(10, 18, 0), &at(11, 10, 0)), 60);
+    assert_eq!(
+        calendar.working_minutes(&at(10, 7, 0), &at(10, 20, 0)),
+        8 * 60
+    );
+    assert_eq!(calendar.working_minutes(&at(10, 12, 0), &at(10, 10, 0)), 0);
+}
+
+#[test]
+fn weekends_are_skipped() {
+    let calendar = Calendar::default();
+    // Friday afternoon to Monday morning
+    assert_eq!(
+        calendar.working_minutes(&at(14, 15, 0), &at(17, 11, 0)),
+        240
+    );
+    // Nothing to do over the weekend itself
+    assert_eq!(calendar.working_minutes(&at(15, 9, 0), &at(16, 17, 0)), 0);
+}
+
+#[test]
+fn holidays_are_skipped() {
+    let calendar = Calendar {
+        holidays: vec![NaiveDate::from_ymd_opt(2025, 3, 17).unwrap()],
+        ..Calendar::default()
+    };
+    assert!(!calendar.is_working_day(NaiveDate::from_ymd_opt(2025, 3, 17).unwrap()));
+    assert_eq!(
+        calendar.working_minutes(&at(14, 15, 0), &at(18, 11, 0)),
+        240
+    );
+}
+
+#[test]
+fn deadlines_after_a_weekend_are_closer_on_friday() {
+    let policy = working_hours(8);
+    let deadline = at(17, 17, 0);
+    // Four working days left on Wednesday morning
+    assert_eq!(policy.priority(&deadline, &at(12, 9, 0)), 5);
+    // Only the end of Friday and Monday left
+    assert_eq!(policy.priority(&deadline, &at(14, 15, 0)), 2);
+    // Counting every hour, Friday looks further away
+    let every_hour = DeadlinePolicy::default();
+    assert_eq!(every_hour.priority(&deadline, &at(14, 15, 0)), 4);
+}
+
+#[test]
+fn no_working_time_left_is_priority_one() {
+    let policy = working_hours(8);
+    assert_eq!(policy.priority(&at(15, 12, 0), &at(14, 18, 0)), 1);
+}
+
+#[test]
+fn calendar_is_saved_with_the_list() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    let policy = DeadlinePolicy {
+        calendar: Some(Calendar {
+            holidays: vec![NaiveDate::from_ymd_opt(2025, 12, 25).unwrap()],
+            ..Calendar::default()
+        }),
+        ..DeadlinePolicy::default()
+    };
+    tasks.set_deadline_policy(policy.clone());
+    assert_eq!(Tasks::load_from(storage).deadline_policy, policy);
+}
+
+#[test]
+fn calendars_without_working_time_count_every_hour() {
+    let every_hour = DeadlinePolicy::default();
+    let deadline = at(17, 17, 0);
+    let expected = every_hour.priority(&deadline, &at(14, 15, 0));
+    let backwards = Calendar {
+        start: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        ..Calendar::default()
+    };
+    let no_days = Calendar {
+        working_days: Vec::new(),
+        ..Calendar::default()
+    };
+    for calendar in [backwards, no_days] {
+        assert!(!calendar.has_working_hours());
+        let policy = DeadlinePolicy {
+            calendar: Some(calendar),
+            ..DeadlinePolicy::default()
+        };
+        assert_eq!(policy.priority(&deadline, &at(14, 15, 0)), expected);
+    }
+}
+
+#[test]
+fn calendars_without_working_time_are_dropped_on_load() {
+    let today = Local::now().date_naive();
+    let every_week = (0..60).map(|x| today + Days::new(x * 7)).collect();
+    let only_holidays = Calendar {
+        working_days: vec![today.weekday()],
+        holidays: every_week,
+        ..Calendar::default()
+    };
+    let backwards = Calendar {
+        end: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        ..Calendar::default()
+    };
+    for calendar in [only_holidays, backwards] {
+        let storage = Rc::new(MemoryStorage::new());
+        let mut tasks = Tasks::load_from(storage.clone());
+        tasks.set_deadline_policy(DeadlinePolicy {
+            calendar: Some(calendar),
+            ..DeadlinePolicy::default()
+        });
+        assert_eq!(Tasks::load_from(storage).deadline_policy.calendar, None);
+    }
+}
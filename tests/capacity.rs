@@ -0,0 +1,69 @@
+use chrono::{DateTime, Duration, Local};
+use nasin::calendar::Calendar;
+use nasin::capacity::check;
+use nasin::scheduler::{DeadlinePolicy, Task, Tasks};
+
+mod common;
+use common::{list, local};
+
+// Friday, 14 March 2025 at 16:00
+fn now() -> DateTime<Local> {
+    local(2025, 3, 14, 16, 0)
+}
+
+fn task(name: &str, due_in_hours: i64, estimate: Option<u32>) -> Task {
+    let mut task = Task::new(
+        String::from(name),
+        1,
+        Some(now() + Duration::hours(due_in_hours)),
+    );
+    task.estimate = estimate;
+    task
+}
+
+// The names of the tasks that won't be done in time
+fn late(tasks: &Tasks) -> Vec<String> {
+    check(tasks, now()).into_iter().map(|x| x.name).collect()
+}
+
+#[test]
+fn work_that_fits_is_fine() {
+    let tasks = list(vec![task("A", 2, Some(60)), task("B", 4, Some(120))]);
+    assert!(check(&tasks, now()).is_empty());
+}
+
+#[test]
+fn later_deadlines_are_at_risk_from_earlier_work() {
+    let tasks = list(vec![task("A", 2, Some(90)), task("B", 3, Some(120))]);
+    let at_risk = check(&tasks, now());
+    assert_eq!(at_risk.len(), 1);
+    assert_eq!(at_risk[0].name, "B");
+    assert_eq!(at_risk[0].work_due, 210);
+    assert_eq!(at_risk[0].available, 180);
+}
+
+#[test]
+fn overdue_work_is_at_risk() {
+    let tasks = list(vec![task("Late", -1, Some(10))]);
+    assert_eq!(late(&tasks), vec!["Late"]);
+}
+
+#[test]
+fn tasks_without_estimates_are_left_out() {
+    let tasks = list(vec![task("A", 1, None), task("B", 1, Some(30))]);
+    assert!(check(&tasks, now()).is_empty());
+}
+
+#[test]
+fn only_working_hours_are_available_with_a_calendar() {
+    // Due Monday at 10:00, with three hours of work left
+    let mut tasks = list(vec![task("Report", 66, Some(180))]);
+    assert!(check(&tasks, now()).is_empty());
+    tasks.set_deadline_policy(DeadlinePolicy {
+        calendar: Some(Calendar::default()),
+        ..DeadlinePolicy::default()
+    });
+    let at_risk = check(&tasks, now());
+    assert_eq!(at_risk.len(), 1);
+    assert_eq!(at_risk[0].available, 120);
+}
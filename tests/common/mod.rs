@@ -0,0 +1,46 @@
+// Fixtures shared by the tests. Each test file builds this on its own and
+// only uses some of it.
+#![allow(dead_code)]
+
+use chrono::{DateTime, Local, NaiveDate};
+use nasin::scheduler::{resolve_local, Task, Tasks};
+
+// Wednesday, 12 March 2025 at 10:00
+pub fn now() -> DateTime<Local> {
+    local(2025, 3, 12, 10, 0)
+}
+
+pub fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+    let naive = NaiveDate::from_ymd_opt(y, m, d)
+        .unwrap()
+        .and_hms_opt(h, min, 0)
+        .unwrap();
+    resolve_local(&Local, naive).unwrap()
+}
+
+pub fn task(name: &str, priority: u8) -> Task {
+    Task::new(String::from(name), priority, None)
+}
+
+pub fn list(tasks: Vec<Task>) -> Tasks {
+    let mut list = Tasks::new();
+    for task in tasks {
+        list.add(task);
+    }
+    list
+}
+
+pub fn names(tasks: &[Task]) -> Vec<&str> {
+    tasks.iter().map(|x| x.name.as_str()).collect()
+}
+
+pub fn find<'a>(tasks: &'a Tasks, name: &str) -> &'a Task {
+    tasks.tasks.iter().find(|x| x.name == name).unwrap()
+}
+
+// Step and return the name of the task that was worked on
+pub fn step(tasks: &mut Tasks) -> String {
+    let name = tasks.tasks[0].name.clone();
+    tasks.step();
+    name
+}
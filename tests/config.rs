@@ -0,0 +1,71 @@
+use chrono::{DateTime, Local};
+use nasin::config::{is_valid_format, Config};
+
+mod common;
+use common::local;
+
+fn at(h: u32, min: u32) -> DateTime<Local> {
+    local(2025, 3, 12, h, min)
+}
+
+#[test]
+fn empty_config_is_the_default() {
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+}
+
+#[test]
+fn partial_config() {
+    let config = Config::from_toml(
+        r#"
+default_priority = 3
+data_dir = "/tmp/nasin"
+
+[confirm]
+finish = true
+
+[keybindings]
+step = ["space", "g s"]
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.default_priority, 3);
+    assert_eq!(config.data_dir(), std::path::PathBuf::from("/tmp/nasin"));
+    assert!(config.confirm.finish);
+    assert!(config.confirm.remove);
+    assert_eq!(config.keybindings["step"], vec!["space", "g s"]);
+    assert_eq!(config.date_format, Config::default().date_format);
+}
+
+#[test]
+fn unusable_values_fall_back() {
+    let config = Config::from_toml("date_format = \"%Q\"\ndefault_priority = 0").unwrap();
+    assert_eq!(config.date_format, Config::default().date_format);
+    assert_eq!(config.default_priority, 1);
+    assert!(Config::from_toml("default_priority = \"high\"").is_err());
+}
+
+#[test]
+fn formats() {
+    assert!(is_valid_format("%d/%m/%Y"));
+    assert!(!is_valid_format("%Q"));
+    let config = Config {
+        date_format: String::from("%d/%m/%Y"),
+        time_format: String::from("%I:%M %p"),
+        ..Config::default()
+    };
+    assert_eq!(config.format_deadline(&at(0, 0)), "12/03/2025");
+    assert_eq!(config.format_deadline(&at(15, 30)), "12/03/2025 03:30 PM");
+}
+
+#[test]
+fn round_trip() {
+    let mut config = Config {
+        default_priority: 4,
+        ..Config::default()
+    };
+    config
+        .keybindings
+        .insert(String::from("finish"), vec![String::from("ctrl-f")]);
+    let text = toml::to_string_pretty(&config).unwrap();
+    assert_eq!(Config::from_toml(&text).unwrap(), config);
+}
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, TimeZone, Timelike};
+use nasin::scheduler::{
+    format_deadline, format_duration, format_timer, parse_deadline, resolve_local, DeadlinePolicy,
+    Task,
+};
+
+fn at(offset_hours: i32, y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(offset_hours * 3600)
+        .unwrap()
+        .with_ymd_and_hms(y, m, d, h, min, 0)
+        .unwrap()
+}
+
+#[test]
+fn due_now_is_overdue() {
+    let now = at(0, 2025, 3, 10, 12, 0);
+    assert_eq!(DeadlinePolicy::default().priority(&now, &now), 0);
+}
+
+#[test]
+fn overdue_without_preempting_is_top_priority() {
+    let policy = DeadlinePolicy {
+        overdue_preempts: false,
+        ..DeadlinePolicy::default()
+    };
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let deadline = now - Duration::weeks(3);
+    assert_eq!(policy.priority(&deadline, &now), 1);
+}
+
+#[test]
+fn long_overdue_is_same_as_just_overdue() {
+    let policy = DeadlinePolicy::default();
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let just = now - Duration::minutes(1);
+    let weeks = now - Duration::weeks(3);
+    assert_eq!(policy.priority(&just, &now), policy.priority(&weeks, &now));
+}
+
+#[test]
+fn one_minute_left_is_priority_one() {
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let deadline = now + Duration::minutes(1);
+    assert_eq!(DeadlinePolicy::default().priority(&deadline, &now), 1);
+}
+
+#[test]
+fn day_boundaries() {
+    let policy = DeadlinePolicy::default();
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let just_under = now + Duration::hours(24) - Duration::minutes(1);
+    let exactly = now + Duration::hours(24);
+    assert_eq!(policy.priority(&just_under, &now), 1);
+    assert_eq!(policy.priority(&exactly, &now), 2);
+}
+
+#[test]
+fn hour_granularity() {
+    let policy = DeadlinePolicy {
+        hours_per_level: 1,
+        ..DeadlinePolicy::default()
+    };
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let deadline = now + Duration::minutes(150);
+    assert_eq!(policy.priority(&deadline, &now), 3);
+}
+
+#[test]
+fn far_deadlines_are_clamped() {
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let deadline = now + Duration::weeks(104);
+    assert_eq!(DeadlinePolicy::default().priority(&deadline, &now), u8::MAX);
+}
+
+#[test]
+fn zero_hours_per_level_does_not_divide_by_zero() {
+    let policy = DeadlinePolicy {
+        hours_per_level: 0,
+        ..DeadlinePolicy::default()
+    };
+    let now = at(0, 2025, 3, 10, 12, 0);
+    let deadline = now + Duration::minutes(90);
+    assert_eq!(policy.priority(&deadline, &now), 2);
+}
+
+#[test]
+fn dst_change_uses_real_time_left() {
+    // Clocks go forward overnight, so midnight to noon is only 11 hours
+    let policy = DeadlinePolicy {
+        hours_per_level: 1,
+        ..DeadlinePolicy::default()
+    };
+    let now = at(1, 2025, 3, 30, 0, 0);
+    let deadline = at(2, 2025, 3, 30, 12, 0);
+    assert_eq!(policy.priority(&deadline, &now), 12);
+}
+
+#[test]
+fn time_zone_does_not_change_priority() {
+    let policy = DeadlinePolicy::default();
+    let now = at(0, 2025, 3, 10, 23, 0);
+    let deadline = at(0, 2025, 3, 11, 1, 0);
+    let shifted_now = now.with_timezone(&FixedOffset::east_opt(9 * 3600).unwrap());
+    let shifted_deadline = deadline.with_timezone(&FixedOffset::east_opt(-5 * 3600).unwrap());
+    assert_eq!(
+        policy.priority(&deadline, &now),
+        policy.priority(&shifted_deadline, &shifted_now)
+    );
+}
+
+#[test]
+fn overdue_state() {
+    let now = Local::now();
+    let task = Task::new(String::from("Late"), 1, Some(now - Duration::days(2)));
+    assert!(task.is_overdue(now));
+    assert_eq!(task.priority, 0);
+    assert_eq!(task.overdue_by(now), Some(Duration::days(2)));
+
+    let task = Task::new(String::from("Fine"), 1, Some(now + Duration::days(2)));
+    assert!(!task.is_overdue(now));
+    assert_eq!(task.overdue_by(now), None);
+
+    let task = Task::new(String::from("None"), 3, None);
+    assert!(!task.is_overdue(now));
+}
+
+#[test]
+fn format_durations() {
+    assert_eq!(format_duration(Duration::days(21)), "21 days");
+    assert_eq!(format_duration(Duration::hours(1)), "1 hour");
+    assert_eq!(format_duration(Duration::minutes(5)), "5 minutes");
+}
+
+#[test]
+fn format_timers() {
+    assert_eq!(format_timer(Duration::seconds(12 * 60 + 5)), "12:05");
+    assert_eq!(format_timer(Duration::zero()), "0:00");
+    assert_eq!(format_timer(Duration::seconds(-45)), "-0:45");
+}
+
+#[test]
+fn parse_date_only() {
+    let deadline = parse_deadline("2025-03-10").unwrap();
+    assert_eq!(
+        deadline.date_naive(),
+        NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()
+    );
+    assert_eq!((deadline.hour(), deadline.minute()), (0, 0));
+    assert_eq!(format_deadline(&deadline), "2025-03-10");
+}
+
+#[test]
+fn parse_date_and_time() {
+    let deadline = parse_deadline(" 2025-03-10 14:00 ").unwrap();
+    assert_eq!(
+        deadline.date_naive(),
+        NaiveDate::from_ymd_opt(2025, 3, 10).unwrap()
+    );
+    assert_eq!((deadline.hour(), deadline.minute()), (14, 0));
+    assert_eq!(format_deadline(&deadline), "2025-03-10 14:00");
+}
+
+#[test]
+fn parse_garbage() {
+    assert!(parse_deadline("tomorrow-ish").is_none());
+    assert!(parse_deadline("2025-13-01").is_none());
+    assert!(parse_deadline("2025-03-10 25:00").is_none());
+}
+
+#[test]
+fn resolve_keeps_the_wall_clock_time() {
+    let tz = FixedOffset::east_opt(-7 * 3600).unwrap();
+    let naive = NaiveDate::from_ymd_opt(2025, 3, 10)
+        .unwrap()
+        .and_hms_opt(23, 30, 0)
+        .unwrap();
+    let resolved = resolve_local(&tz, naive).unwrap();
+    assert_eq!(resolved.naive_local(), naive);
+}
@@ -0,0 +1,108 @@
+use chrono::{Duration, Local};
+use nasin::scheduler::{AgingPolicy, Changes, Task, Tasks};
+
+mod common;
+use common::{find, list, task};
+
+fn abc() -> Tasks {
+    list(vec![task("a", 2), task("b", 3), task("c", 3)])
+}
+
+#[test]
+fn raising_and_lowering_moves_the_base_priority() {
+    let mut tasks = abc();
+    let c = find(&tasks, "c").id;
+    assert_eq!(tasks.raise_priority(&[c]), 1);
+    assert_eq!(
+        (
+            find(&tasks, "c").priority,
+            find(&tasks, "c").base_priority()
+        ),
+        (2, 2)
+    );
+    assert_eq!(tasks.raise_priority(&[c]), 1);
+    assert_eq!(tasks.tasks[0].name, "c");
+    // Priority 1 is as high as it goes, short of being overdue
+    assert_eq!(tasks.raise_priority(&[c]), 0);
+    assert_eq!(tasks.lower_priority(&[c]), 1);
+    assert_eq!(find(&tasks, "c").base_priority(), 2);
+}
+
+#[test]
+fn promotions_are_kept_when_shifting() {
+    let mut tasks = abc();
+    tasks.set_aging_policy(AgingPolicy {
+        boosted_per_step: 3,
+        ..AgingPolicy::default()
+    });
+    tasks.step();
+    let b = find(&tasks, "b").clone();
+    assert_eq!((b.priority, b.base_priority()), (2, 3));
+    tasks.lower_priority(&[b.id]);
+    let lowered = find(&tasks, "b");
+    assert_eq!((lowered.priority, lowered.base_priority()), (3, 4));
+    assert_eq!(lowered.steps_waited(), b.steps_waited());
+}
+
+#[test]
+fn several_tasks_shift_as_one_change() {
+    let mut tasks = abc();
+    let ids: Vec<u64> = tasks.tasks.iter().map(|x| x.id).collect();
+    assert_eq!(tasks.lower_priority(&ids), 3);
+    assert_eq!(find(&tasks, "a").priority, 3);
+    assert!(tasks.undo());
+    assert_eq!(find(&tasks, "a").priority, 2);
+    assert_eq!(find(&tasks, "b").priority, 3);
+}
+
+#[test]
+fn tasks_with_deadlines_keep_their_priority() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(
+        String::from("due"),
+        5,
+        Some(Local::now() + Duration::days(3)),
+    ));
+    let due = tasks.tasks[0].clone();
+    assert_eq!(tasks.raise_priority(&[due.id]), 0);
+    assert_eq!(tasks.tasks[0].priority, due.priority);
+}
+
+#[test]
+fn deadlines_can_be_set_and_cleared() {
+    let mut tasks = abc();
+    let c = find(&tasks, "c").id;
+    let deadline = Local::now() + Duration::hours(2);
+    tasks.change(
+        &[c],
+        &Changes {
+            deadline: Some(Some(deadline)),
+            ..Changes::default()
+        },
+    );
+    assert_eq!(tasks.tasks[0].name, "c");
+    let urgent = find(&tasks, "c").base_priority();
+    assert!(urgent < 2);
+    tasks.change(
+        &[c],
+        &Changes {
+            deadline: Some(None),
+            ..Changes::default()
+        },
+    );
+    let c = find(&tasks, "c");
+    assert_eq!(c.deadline, None);
+    assert_eq!((c.priority, c.base_priority()), (urgent, urgent));
+}
+
+#[test]
+fn renaming_can_be_undone() {
+    let mut tasks = abc();
+    let a = find(&tasks, "a").id;
+    assert!(tasks.rename(a, "  apples "));
+    assert_eq!(tasks.find(a).unwrap().name, "apples");
+    assert!(!tasks.rename(a, "   "));
+    assert!(!tasks.rename(a, "apples"));
+    assert!(tasks.undo());
+    assert_eq!(tasks.find(a).unwrap().name, "a");
+}
@@ -0,0 +1,122 @@
+use std::rc::Rc;
+
+use chrono::{Duration, Local};
+use nasin::parser;
+use nasin::scheduler::{AgingPolicy, Changes, Energy, Task, Tasks};
+use nasin::storage::MemoryStorage;
+
+mod common;
+use common::names;
+
+fn task(name: &str, priority: u8, energy: Option<Energy>) -> Task {
+    let mut task = common::task(name, priority);
+    task.energy = energy;
+    task
+}
+
+// A deep piece of work, something easy and something that doesn't say,
+// without promotions getting in the way
+fn mixed() -> Tasks {
+    let mut tasks = Tasks::new();
+    tasks.set_aging_policy(AgingPolicy {
+        boosted_per_step: 0,
+        ..AgingPolicy::default()
+    });
+    tasks.add(task("write", 1, Some(Energy::High)));
+    tasks.add(task("tidy", 2, Some(Energy::Low)));
+    tasks.add(task("email", 2, None));
+    tasks
+}
+
+#[test]
+fn energy_is_ignored_until_set() {
+    let tasks = mixed();
+    assert_eq!(tasks.tasks[0].name, "write");
+}
+
+#[test]
+fn low_energy_holds_back_demanding_tasks() {
+    let mut tasks = mixed();
+    tasks.set_energy(Some(Energy::Low));
+    // Two levels off, so it goes as if it were priority 3
+    assert_eq!(names(&tasks.tasks), vec!["tidy", "email", "write"]);
+}
+
+#[test]
+fn high_energy_favours_demanding_tasks() {
+    let mut tasks = mixed();
+    tasks.add(task("plan", 1, Some(Energy::Medium)));
+    tasks.set_energy(Some(Energy::High));
+    assert_eq!(tasks.tasks[0].name, "write");
+    assert_eq!(tasks.tasks.last().unwrap().name, "tidy");
+}
+
+#[test]
+fn medium_energy_is_one_level_from_either() {
+    let mut tasks = mixed();
+    tasks.set_energy(Some(Energy::Medium));
+    // write is held back to priority 2, with email, and tidy to 3
+    assert_eq!(names(&tasks.tasks), vec!["write", "email", "tidy"]);
+    tasks.step();
+    assert_eq!(names(&tasks.tasks), vec!["email", "write", "tidy"]);
+}
+
+#[test]
+fn overdue_tasks_go_first_whatever_the_energy() {
+    let mut tasks = Tasks::new();
+    tasks.add(task("tidy", 1, Some(Energy::Low)));
+    let mut late = Task::new(
+        String::from("late"),
+        1,
+        Some(Local::now() - Duration::hours(1)),
+    );
+    late.energy = Some(Energy::High);
+    tasks.add(late);
+    tasks.set_energy(Some(Energy::Low));
+    assert_eq!(names(&tasks.tasks), vec!["late", "tidy"]);
+}
+
+#[test]
+fn priorities_are_left_alone() {
+    let mut tasks = mixed();
+    tasks.set_energy(Some(Energy::Low));
+    tasks.step();
+    let write = tasks.tasks.iter().find(|x| x.name == "write").unwrap();
+    assert_eq!(write.priority, 1);
+    tasks.set_energy(None);
+    tasks.step();
+    assert_eq!(tasks.tasks[0].name, "write");
+}
+
+#[test]
+fn energy_can_be_changed_and_quick_added() {
+    let parsed = parser::parse("Draft chapter @high ~2h", Local::now());
+    assert_eq!(parsed.name, "Draft chapter");
+    assert_eq!(parsed.energy, Some(Energy::High));
+    let mut tasks = Tasks::new();
+    tasks.add(parsed.into_task());
+    let id = tasks.tasks[0].id;
+    tasks.change(
+        &[id],
+        &Changes {
+            energy: Some(Some(Energy::Low)),
+            ..Changes::default()
+        },
+    );
+    assert_eq!(tasks.find(id).unwrap().energy, Some(Energy::Low));
+    assert_eq!(
+        parser::parse("@med", Local::now()).into_changes().energy,
+        Some(Some(Energy::Medium))
+    );
+}
+
+#[test]
+fn current_energy_is_saved_with_the_list() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    tasks.add(task("write", 1, Some(Energy::High)));
+    tasks.set_energy(Some(Energy::Low));
+    let loaded = Tasks::load_from(storage);
+    assert_eq!(loaded.energy, Some(Energy::Low));
+    assert_eq!(loaded.tasks[0].energy, Some(Energy::High));
+}
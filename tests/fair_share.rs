@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, Local};
+
+use nasin::project::{FairShare, Group};
+use nasin::scheduler::{Task, Tasks};
+
+// Step many times and count how often each group was worked on
+fn run(tasks: &mut Tasks, steps: usize) -> BTreeMap<Group, usize> {
+    let mut worked = BTreeMap::new();
+    for _ in 0..steps {
+        let task = &tasks.tasks[0];
+        let group = match tasks.fair_share {
+            FairShare::Tags => task.tags.first().cloned().map(Group::Tag),
+            _ => task.project.map(Group::Project),
+        };
+        *worked.entry(group.unwrap_or(Group::Other)).or_default() += 1;
+        tasks.step();
+    }
+    worked
+}
+
+// Within 2% of the share it should have had
+fn assert_near(got: usize, want: usize) {
+    let off = got.abs_diff(want);
+    assert!(off <= want / 50 + 1, "{} steps instead of {}", got, want);
+}
+
+fn add(tasks: &mut Tasks, name: &str, priority: u8, project: Option<u64>, tag: Option<&str>) {
+    let mut task = Task::new(String::from(name), priority, None);
+    task.project = project;
+    task.tags = tag.map(String::from).into_iter().collect();
+    tasks.add(task);
+}
+
+// Three projects with a few tasks each, the first one the most urgent
+fn three_projects() -> (Tasks, Vec<u64>) {
+    let mut tasks = Tasks::new();
+    let ids: Vec<u64> = ["Big", "Medium", "Small"]
+        .iter()
+        .map(|x| tasks.project_named(x))
+        .collect();
+    for (i, id) in ids.iter().enumerate() {
+        for n in 0..3 {
+            add(
+                &mut tasks,
+                &format!("{}{}", i, n),
+                i as u8 + 1,
+                Some(*id),
+                None,
+            );
+        }
+    }
+    (tasks, ids)
+}
+
+fn set_weight(tasks: &mut Tasks, id: u64, weight: u32) {
+    let mut project = tasks.project(id).unwrap().clone();
+    project.weight = weight;
+    tasks.update_project(project);
+}
+
+#[test]
+fn equal_weights_share_equally() {
+    let (mut tasks, ids) = three_projects();
+    tasks.set_fair_share(FairShare::Projects);
+    let worked = run(&mut tasks, 300);
+    for id in ids {
+        assert_near(worked[&Group::Project(id)], 100);
+    }
+}
+
+#[test]
+fn service_follows_the_weights_over_a_long_run() {
+    let (mut tasks, ids) = three_projects();
+    set_weight(&mut tasks, ids[0], 1);
+    set_weight(&mut tasks, ids[1], 2);
+    set_weight(&mut tasks, ids[2], 3);
+    tasks.set_fair_share(FairShare::Projects);
+    let worked = run(&mut tasks, 1200);
+    for (id, share) in ids.iter().zip([200, 400, 600]) {
+        assert_near(worked[&Group::Project(*id)], share);
+    }
+}
+
+#[test]
+fn recent_service_is_tracked_per_group() {
+    let (mut tasks, ids) = three_projects();
+    set_weight(&mut tasks, ids[2], 2);
+    tasks.set_fair_share(FairShare::Projects);
+    run(&mut tasks, 400);
+    let service = tasks.service();
+    assert_eq!(service.values().sum::<usize>(), 100);
+    assert!(service[&Group::Project(ids[2])] >= 48);
+    assert_eq!(tasks.weight(&Group::Project(ids[2])), 2);
+    assert_eq!(tasks.weight(&Group::Other), 1);
+}
+
+#[test]
+fn tags_share_by_their_weights() {
+    let mut tasks = Tasks::new();
+    for n in 0..3 {
+        add(&mut tasks, &format!("w{}", n), 1, None, Some("work"));
+        add(&mut tasks, &format!("h{}", n), 2, None, Some("home"));
+        add(&mut tasks, &format!("o{}", n), 3, None, None);
+    }
+    tasks.set_tag_weight("work", 2);
+    tasks.set_fair_share(FairShare::Tags);
+    let worked = run(&mut tasks, 400);
+    assert_near(worked[&Group::Tag(String::from("work"))], 200);
+    assert_near(worked[&Group::Tag(String::from("home"))], 100);
+    assert_near(worked[&Group::Other], 100);
+}
+
+#[test]
+fn a_new_group_is_not_starved() {
+    let (mut tasks, ids) = three_projects();
+    tasks.set_fair_share(FairShare::Projects);
+    run(&mut tasks, 90);
+    let late = tasks.project_named("Late");
+    add(&mut tasks, "late", 9, Some(late), None);
+    // It has had none of its share lately, so it catches up first
+    let worked = run(&mut tasks, 10);
+    assert_eq!(worked[&Group::Project(late)], 10);
+    let worked = run(&mut tasks, 40);
+    for id in ids {
+        assert!(worked[&Group::Project(id)] >= 5);
+    }
+}
+
+#[test]
+fn overdue_tasks_go_ahead_of_sharing() {
+    let mut tasks = Tasks::new();
+    let busy = tasks.project_named("Busy");
+    let idle = tasks.project_named("Idle");
+    add(&mut tasks, "busy", 1, Some(busy), None);
+    for _ in 0..5 {
+        tasks.step();
+    }
+    add(&mut tasks, "idle", 8, Some(idle), None);
+    let mut late = Task::new(
+        String::from("late"),
+        1,
+        Some(Local::now() - Duration::hours(1)),
+    );
+    late.project = Some(busy);
+    tasks.add(late);
+    tasks.set_fair_share(FairShare::Projects);
+    assert_eq!(tasks.tasks[0].name, "late");
+    // Once it's done, the idle project gets its turn
+    tasks.step_and_finish();
+    assert_eq!(tasks.tasks[0].name, "idle");
+}
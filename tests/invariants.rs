@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Local};
+use nasin::scheduler::{Task, Tasks};
+use proptest::prelude::*;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Add {
+        priority: u8,
+        deadline_days: Option<i64>,
+    },
+    Step,
+    Finish,
+    TogglePause(usize),
+    Remove(usize),
+    Move(usize, usize),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        1 => (1u8..=10, proptest::option::of(1i64..60)).prop_map(|(priority, deadline_days)| {
+            Op::Add {
+                priority,
+                deadline_days,
+            }
+        }),
+        4 => Just(Op::Step),
+        1 => Just(Op::Finish),
+        1 => any::<usize>().prop_map(Op::TogglePause),
+        1 => any::<usize>().prop_map(Op::Remove),
+        1 => (any::<usize>(), any::<usize>()).prop_map(|(from, to)| Op::Move(from, to)),
+    ]
+}
+
+fn apply(tasks: &mut Tasks, op: &Op) {
+    match op {
+        Op::Add {
+            priority,
+            deadline_days,
+        } => {
+            let deadline = deadline_days.map(|x| Local::now() + Duration::days(x));
+            let name = format!("Task {}", tasks.tasks.len());
+            tasks.add(Task::new(name, *priority, deadline));
+        }
+        Op::Step => {
+            tasks.step();
+        }
+        Op::Finish => {
+            tasks.step_and_finish();
+        }
+        Op::TogglePause(i) if !tasks.tasks.is_empty() => {
+            let task = tasks.tasks[i % tasks.tasks.len()].clone();
+            tasks.toggle_pause(&task);
+        }
+        Op::Remove(i) if !tasks.tasks.is_empty() => {
+            let task = tasks.tasks[i % tasks.tasks.len()].clone();
+            tasks.remove(task);
+        }
+        Op::Move(from, to) if !tasks.tasks.is_empty() => {
+            let id = tasks.tasks[from % tasks.tasks.len()].id;
+            tasks.move_task(id, to % tasks.tasks.len());
+        }
+        _ => {}
+    }
+}
+
+// Paused tasks by id, with their priority and steps waited
+fn paused(tasks: &Tasks) -> HashMap<u64, (u8, u32)> {
+    tasks
+        .tasks
+        .iter()
+        .filter(|x| x.paused)
+        .map(|x| (x.id, (x.priority, x.steps_waited())))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn random_operations_keep_invariants(ops in proptest::collection::vec(op(), 0..80)) {
+        let mut tasks = Tasks::new();
+        for op in &ops {
+            let before = paused(&tasks);
+            apply(&mut tasks, op);
+            let after = paused(&tasks);
+
+            if matches!(op, Op::Step | Op::Finish) {
+                for (id, state) in &before {
+                    if let Some(now) = after.get(id) {
+                        prop_assert_eq!(state, now, "paused task {} aged", id);
+                    }
+                }
+            }
+            for task in &tasks.tasks {
+                // Nothing here is overdue, so priority 0 never comes up
+                prop_assert!(task.priority >= 1, "{:?}", task);
+                prop_assert!(task.priority <= task.base_priority(), "{:?}", task);
+            }
+            let mut sorted = tasks.tasks.clone();
+            sorted.sort();
+            prop_assert!(sorted == tasks.tasks, "list is out of order");
+        }
+    }
+
+    #[test]
+    fn every_unpaused_task_eventually_runs(
+        priorities in proptest::collection::vec(1u8..=10, 1..8),
+        paused_mask in proptest::collection::vec(any::<bool>(), 8),
+    ) {
+        let mut tasks = Tasks::new();
+        for (i, priority) in priorities.iter().enumerate() {
+            tasks.add(Task::new(format!("Task {}", i), *priority, None));
+        }
+        for (i, pause) in paused_mask.iter().take(priorities.len()).enumerate() {
+            if *pause {
+                let task = tasks.tasks.iter().find(|x| x.name == format!("Task {}", i)).unwrap().clone();
+                tasks.toggle_pause(&task);
+            }
+        }
+        let waiting: Vec<u64> = tasks.tasks.iter().filter(|x| !x.paused).map(|x| x.id).collect();
+        let mut ran = Vec::new();
+        for _ in 0..priorities.len() * 20 {
+            if let Some(head) = tasks.tasks.first().filter(|x| !x.paused) {
+                ran.push(head.id);
+            }
+            tasks.step();
+        }
+        for id in &waiting {
+            prop_assert!(ran.contains(id), "task {} never ran", id);
+        }
+        for task in tasks.tasks.iter().filter(|x| x.paused) {
+            prop_assert!(!ran.contains(&task.id), "paused task {} ran", task.id);
+        }
+    }
+}
+
+#[test]
+fn stepping_with_everything_paused_does_nothing() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(String::from("Only"), 3, None));
+    let task = tasks.tasks[0].clone();
+    tasks.toggle_pause(&task);
+    let history = tasks.history.len();
+    assert_eq!(tasks.step(), None);
+    assert_eq!(tasks.step_and_finish(), None);
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.history.len(), history);
+}
+
+#[test]
+fn stepping_the_last_task() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(String::from("Only"), 3, None));
+    for _ in 0..3 {
+        tasks.step();
+    }
+    assert_eq!(tasks.tasks[0].priority, 3);
+    assert_eq!(tasks.tasks[0].steps_waited(), 0);
+}
+
+#[test]
+fn finishing_skips_paused_tasks_when_aging() {
+    let mut tasks = Tasks::new();
+    for (name, priority) in [("A", 1), ("B", 1), ("Paused", 5), ("Waiting", 5)] {
+        tasks.add(Task::new(String::from(name), priority, None));
+    }
+    let paused = tasks
+        .tasks
+        .iter()
+        .find(|x| x.name == "Paused")
+        .unwrap()
+        .clone();
+    tasks.toggle_pause(&paused);
+    let promoted = tasks.step_and_finish().unwrap();
+    assert_eq!(promoted.name, "Waiting");
+}
+
+// The README algorithm on a fixed list. If this changes, the order
+// tasks are worked in has changed too.
+#[test]
+fn golden_trace() {
+    let mut tasks = Tasks::new();
+    for (name, priority) in [("A", 1), ("B", 2), ("C", 3), ("D", 5)] {
+        tasks.add(Task::new(String::from(name), priority, None));
+    }
+    let mut trace = Vec::new();
+    for i in 0..24 {
+        trace.push(tasks.tasks[0].name.clone());
+        if i == 10 {
+            let c = tasks.tasks.iter().find(|x| x.name == "C").unwrap().clone();
+            tasks.toggle_pause(&c);
+        }
+        tasks.step();
+    }
+    assert_eq!(
+        trace.join(" "),
+        "A A A B A C A B A D A B A A B A D B A A B A B A"
+    );
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use nasin::keymap::{Action, Feed, Key, Keymap};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn press(code: KeyCode, modifiers: KeyModifiers) -> Key {
+    Key::from_event(&KeyEvent::new(code, modifiers))
+}
+
+fn keymap(bindings: &[(&str, &[&str])]) -> Keymap {
+    let config: BTreeMap<String, Vec<String>> = bindings
+        .iter()
+        .map(|(action, keys)| {
+            let keys = keys.iter().map(|x| String::from(*x)).collect();
+            (String::from(*action), keys)
+        })
+        .collect();
+    Keymap::new(&config)
+}
+
+fn feed(keymap: &mut Keymap, c: char) -> Feed {
+    keymap.feed(&KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+}
+
+#[test]
+fn keys_with_modifiers() {
+    let table = [
+        ("j", press(KeyCode::Char('j'), KeyModifiers::NONE)),
+        ("ctrl-d", press(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+        ("alt-x", press(KeyCode::Char('x'), KeyModifiers::ALT)),
+        (
+            "alt-shift-Up",
+            press(KeyCode::Up, KeyModifiers::ALT | KeyModifiers::SHIFT),
+        ),
+        ("Enter", press(KeyCode::Enter, KeyModifiers::NONE)),
+        ("space", press(KeyCode::Char(' '), KeyModifiers::NONE)),
+    ];
+    for (text, key) in table {
+        assert_eq!(Key::parse(text), Some(key), "{}", text);
+    }
+    assert_eq!(Key::parse("ctrl-"), None);
+    assert_eq!(Key::parse("hyper-x"), None);
+}
+
+#[test]
+fn a_lone_minus_is_the_minus_key() {
+    assert_eq!(
+        Key::parse("-"),
+        Some(press(KeyCode::Char('-'), KeyModifiers::NONE))
+    );
+    assert_eq!(
+        Key::parse("ctrl--"),
+        Some(press(KeyCode::Char('-'), KeyModifiers::CONTROL))
+    );
+}
+
+#[test]
+fn shift_is_part_of_the_character() {
+    let capital = press(KeyCode::Char('S'), KeyModifiers::SHIFT);
+    assert_eq!(Key::parse("shift-s"), Some(capital));
+    assert_eq!(Key::parse("S"), Some(capital));
+    assert_eq!(
+        Key::parse("ctrl-shift-s"),
+        Some(press(
+            KeyCode::Char('S'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT
+        ))
+    );
+}
+
+#[test]
+fn function_keys() {
+    for n in 1..=12 {
+        let key = press(KeyCode::F(n), KeyModifiers::NONE);
+        assert_eq!(Key::parse(&format!("F{}", n)), Some(key));
+        assert_eq!(Key::parse(&format!("f{}", n)), Some(key));
+    }
+    assert_eq!(Key::parse("F0"), None);
+    assert_eq!(Key::parse("F13"), None);
+}
+
+#[test]
+fn backtab_matches_however_it_is_reported() {
+    let reported = press(KeyCode::BackTab, KeyModifiers::SHIFT);
+    assert_eq!(press(KeyCode::BackTab, KeyModifiers::NONE), reported);
+    assert_eq!(Key::parse("backtab"), Some(reported));
+    assert_eq!(Key::parse("shift-tab"), Some(reported));
+    let mut keymap = keymap(&[("today", &["backtab"])]);
+    let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+    assert_eq!(keymap.feed(&event), Feed::Action(Action::Today));
+}
+
+#[test]
+fn the_defaults_do_not_clash() {
+    let keymap = keymap(&[]);
+    assert!(keymap.problems.is_empty(), "{:?}", keymap.problems);
+    assert_eq!(keymap.keys(Action::Down), "j/Down");
+}
+
+#[test]
+fn configured_keys_replace_the_defaults() {
+    let keymap = keymap(&[("finish", &["j"])]);
+    assert!(keymap.problems.is_empty());
+    assert_eq!(keymap.keys(Action::Finish), "j");
+    // Down keeps its other key
+    assert_eq!(keymap.keys(Action::Down), "Down");
+}
+
+#[test]
+fn clashing_keys_are_reported() {
+    let keymap = keymap(&[("step", &["x"]), ("finish", &["x"])]);
+    assert_eq!(
+        keymap.problems,
+        vec![String::from("x for finish clashes with x for step")]
+    );
+    assert_eq!(keymap.keys(Action::Step), "x");
+    assert_eq!(keymap.keys(Action::Finish), "");
+}
+
+#[test]
+fn a_key_clashes_with_sequences_starting_with_it() {
+    let longer = keymap(&[("step", &["g"]), ("finish", &["g g"])]);
+    assert_eq!(
+        longer.problems,
+        vec![String::from("g g for finish clashes with g for step")]
+    );
+    let shorter = keymap(&[("step", &["g g"]), ("finish", &["g"])]);
+    assert_eq!(
+        shorter.problems,
+        vec![String::from("g for finish clashes with g g for step")]
+    );
+}
+
+#[test]
+fn problems_with_the_config_are_reported() {
+    let keymap = keymap(&[("jump", &["x"]), ("step", &["ctrl-nothing"])]);
+    assert_eq!(
+        keymap.problems,
+        vec![
+            String::from("Unknown action \"jump\""),
+            String::from("Can't understand \"ctrl-nothing\" for step"),
+        ]
+    );
+}
+
+#[test]
+fn sequences() {
+    let mut keymap = keymap(&[("finish", &["g f"])]);
+    assert_eq!(feed(&mut keymap, 'g'), Feed::Pending);
+    assert_eq!(keymap.pending(), "g");
+    assert_eq!(feed(&mut keymap, 'f'), Feed::Action(Action::Finish));
+    assert_eq!(keymap.pending(), "");
+}
+
+#[test]
+fn a_broken_sequence_starts_again_from_the_last_key() {
+    let mut keymap = keymap(&[("finish", &["g f"])]);
+    assert_eq!(feed(&mut keymap, 'g'), Feed::Pending);
+    assert_eq!(feed(&mut keymap, 'j'), Feed::Action(Action::Down));
+    assert_eq!(feed(&mut keymap, 'g'), Feed::Pending);
+    assert_eq!(feed(&mut keymap, 'g'), Feed::Pending);
+    assert_eq!(feed(&mut keymap, 'f'), Feed::Action(Action::Finish));
+    assert_eq!(feed(&mut keymap, 'g'), Feed::Pending);
+    assert_eq!(feed(&mut keymap, 'x'), Feed::Unbound);
+    assert_eq!(keymap.pending(), "");
+}
@@ -0,0 +1,65 @@
+use chrono::{Duration, Local};
+use nasin::notifications::{NotificationKind, NotificationSettings, Notifier};
+use nasin::scheduler::{Recurrence, RecurrenceUnit, Task, Tasks};
+
+fn kinds(notifier: &mut Notifier, tasks: &Tasks, minutes: i64) -> Vec<NotificationKind> {
+    let now = Local::now() + Duration::minutes(minutes);
+    notifier.poll(tasks, now).iter().map(|x| x.kind).collect()
+}
+
+#[test]
+fn deadlines_are_announced_once() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(
+        String::from("Report"),
+        1,
+        Some(Local::now() + Duration::hours(2)),
+    ));
+    let mut notifier = Notifier::new(NotificationSettings::default());
+    assert_eq!(
+        kinds(&mut notifier, &tasks, 0),
+        vec![NotificationKind::DeadlineApproaching]
+    );
+    assert!(kinds(&mut notifier, &tasks, 1).is_empty());
+    // Renaming it doesn't announce it again
+    let id = tasks.tasks[0].id;
+    tasks.rename(id, "Quarterly report");
+    assert!(kinds(&mut notifier, &tasks, 2).is_empty());
+}
+
+#[test]
+fn each_occurrence_of_a_recurring_task_is_announced() {
+    let mut chore = Task::new(
+        String::from("Water plants"),
+        1,
+        Some(Local::now() + Duration::hours(1)),
+    );
+    chore.recurrence = Some(Recurrence {
+        every: 1,
+        unit: RecurrenceUnit::Day,
+    });
+    let mut tasks = Tasks::new();
+    tasks.add(chore);
+    let mut notifier = Notifier::new(NotificationSettings::default());
+    assert_eq!(kinds(&mut notifier, &tasks, 0).len(), 1);
+    tasks.step_and_finish();
+    // The next one is due a day later
+    assert!(kinds(&mut notifier, &tasks, 120).contains(&NotificationKind::DeadlineApproaching));
+}
+
+#[test]
+fn work_sessions_only_end_while_something_is_being_worked_on() {
+    let mut tasks = Tasks::new();
+    let mut notifier = Notifier::new(NotificationSettings::default());
+    let later = NotificationSettings::default().quantum_minutes as i64 + 1;
+    assert!(kinds(&mut notifier, &tasks, later).is_empty());
+    tasks.add(Task::new(String::from("Read"), 1, None));
+    tasks.toggle_pause(&tasks.tasks[0].clone());
+    assert!(kinds(&mut notifier, &tasks, later).is_empty());
+    tasks.toggle_pause(&tasks.tasks[0].clone());
+    assert_eq!(
+        kinds(&mut notifier, &tasks, later),
+        vec![NotificationKind::QuantumEnded]
+    );
+    assert!(kinds(&mut notifier, &tasks, later).is_empty());
+}
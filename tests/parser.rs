@@ -0,0 +1,216 @@
+use nasin::parser::parse;
+use nasin::scheduler::{Recurrence, RecurrenceUnit, Tasks};
+
+mod common;
+use common::{local, now};
+
+fn every(every: u32, unit: RecurrenceUnit) -> Option<Recurrence> {
+    Some(Recurrence { every, unit })
+}
+
+#[test]
+fn full_example() {
+    let parsed = parse("Call dentist tomorrow 3pm !2 #health every 6 months", now());
+    assert_eq!(parsed.name, "Call dentist");
+    assert_eq!(parsed.deadline, Some(local(2025, 3, 13, 15, 0)));
+    assert_eq!(parsed.priority, Some(2));
+    assert_eq!(parsed.tags, vec![String::from("health")]);
+    assert_eq!(parsed.recurrence, every(6, RecurrenceUnit::Month));
+}
+
+#[test]
+fn deadlines() {
+    let table = [
+        ("Plain task", None),
+        ("Pay rent today", Some(local(2025, 3, 12, 0, 0))),
+        ("Pay rent tomorrow", Some(local(2025, 3, 13, 0, 0))),
+        ("Movie tonight", Some(local(2025, 3, 12, 20, 0))),
+        ("Submit report at 14:00", Some(local(2025, 3, 12, 14, 0))),
+        ("Submit report 9am", Some(local(2025, 3, 13, 9, 0))),
+        ("Lunch noon", Some(local(2025, 3, 12, 12, 0))),
+        ("Call 12am", Some(local(2025, 3, 13, 0, 0))),
+        ("Call 12pm", Some(local(2025, 3, 12, 12, 0))),
+        ("Call at 3:30pm", Some(local(2025, 3, 12, 15, 30))),
+        ("Essay due friday", Some(local(2025, 3, 14, 0, 0))),
+        ("Essay on fri", Some(local(2025, 3, 14, 0, 0))),
+        ("Meeting wednesday", Some(local(2025, 3, 19, 0, 0))),
+        ("Meeting next monday 9am", Some(local(2025, 3, 17, 9, 0))),
+        ("Taxes by 2025-04-15", Some(local(2025, 4, 15, 0, 0))),
+        ("Taxes 2025-04-15 17:00", Some(local(2025, 4, 15, 17, 0))),
+        ("Taxes on tomorrow", Some(local(2025, 3, 13, 0, 0))),
+        ("Renew in 3 days", Some(local(2025, 3, 15, 0, 0))),
+        ("Renew in two weeks", Some(local(2025, 3, 26, 0, 0))),
+        ("Renew in a month", Some(local(2025, 4, 12, 0, 0))),
+        ("Renew next year", Some(local(2026, 3, 12, 0, 0))),
+        ("x in 400000000 years", None),
+    ];
+    for (text, deadline) in table {
+        assert_eq!(parse(text, now()).deadline, deadline, "{}", text);
+    }
+}
+
+#[test]
+fn names() {
+    let table = [
+        ("Call dentist tomorrow", "Call dentist"),
+        ("Check in with Sam", "Check in with Sam"),
+        ("Lunch at Joe's", "Lunch at Joe's"),
+        ("Fix sat nav", "Fix sat nav"),
+        ("Buy a mat", "Buy a mat"),
+        ("Water plants every 3 days", "Water plants"),
+        ("Email   Kim   !3", "Email Kim"),
+        ("!1 #work Budget", "Budget"),
+        ("Say !wow", "Say !wow"),
+        ("Priority !0 stays", "Priority !0 stays"),
+        ("Lone # sign", "Lone # sign"),
+        ("x in 400000000 years", "x in 400000000 years"),
+        ("x every 400000000 years", "x every 400000000 years"),
+    ];
+    for (text, name) in table {
+        assert_eq!(parse(text, now()).name, name, "{}", text);
+    }
+}
+
+#[test]
+fn priorities_and_tags() {
+    let parsed = parse("Budget !12 #work #Finance", now());
+    assert_eq!(parsed.priority, Some(12));
+    assert_eq!(
+        parsed.tags,
+        vec![String::from("work"), String::from("Finance")]
+    );
+    assert_eq!(parse("Budget !300", now()).priority, None);
+    assert_eq!(parse("Budget", now()).priority, None);
+}
+
+#[test]
+fn recurrences() {
+    let table = [
+        ("Stretch daily", every(1, RecurrenceUnit::Day)),
+        ("Review weekly", every(1, RecurrenceUnit::Week)),
+        ("Pay bills monthly", every(1, RecurrenceUnit::Month)),
+        ("Checkup annually", every(1, RecurrenceUnit::Year)),
+        ("Stretch every day", every(1, RecurrenceUnit::Day)),
+        ("Water every 3 days", every(3, RecurrenceUnit::Day)),
+        ("Bins every two weeks", every(2, RecurrenceUnit::Week)),
+        ("Bins every monday", every(1, RecurrenceUnit::Week)),
+        ("Spin every 0 days", None),
+        ("x every 400000000 years", None),
+        ("Everything else", None),
+    ];
+    for (text, recurrence) in table {
+        assert_eq!(parse(text, now()).recurrence, recurrence, "{}", text);
+    }
+}
+
+#[test]
+fn recurrences_too_far_apart_stay_put() {
+    let far = Recurrence {
+        every: u32::MAX,
+        unit: RecurrenceUnit::Year,
+    };
+    assert_eq!(far.next(now()), now());
+}
+
+#[test]
+fn recurrence_without_date_starts_from_now() {
+    let parsed = parse("Water every 3 days", now());
+    assert_eq!(parsed.deadline, Some(local(2025, 3, 15, 10, 0)));
+    let parsed = parse("Bins every monday", now());
+    assert_eq!(parsed.deadline, Some(local(2025, 3, 17, 0, 0)));
+}
+
+#[test]
+fn start_dates() {
+    let table = [
+        (
+            "Renew passport after friday",
+            Some(local(2025, 3, 14, 0, 0)),
+        ),
+        (
+            "Renew passport start next week",
+            Some(local(2025, 3, 19, 0, 0)),
+        ),
+        (
+            "Renew passport after 2025-04-01",
+            Some(local(2025, 4, 1, 0, 0)),
+        ),
+        (
+            "Renew passport start in 3 days",
+            Some(local(2025, 3, 15, 0, 0)),
+        ),
+        (
+            "Renew passport after tomorrow",
+            Some(local(2025, 3, 13, 0, 0)),
+        ),
+        ("Call after lunch", None),
+    ];
+    for (text, start) in table {
+        assert_eq!(parse(text, now()).start, start, "{}", text);
+    }
+    let parsed = parse("Renew passport after friday due 2025-04-15", now());
+    assert_eq!(parsed.name, "Renew passport");
+    assert_eq!(parsed.deadline, Some(local(2025, 4, 15, 0, 0)));
+    assert_eq!(parse("Call after lunch", now()).name, "Call after lunch");
+}
+
+#[test]
+fn tasks_that_start_later_are_snoozed_until_then() {
+    let task = parse("Renew passport after friday", now()).into_task();
+    assert!(task.paused);
+    assert_eq!(task.snoozed_until, Some(local(2025, 3, 14, 0, 0)));
+    let mut tasks = Tasks::new();
+    tasks.add(task);
+    tasks.wake(local(2025, 3, 14, 8, 0));
+    assert!(!tasks.tasks[0].paused);
+}
+
+#[test]
+fn into_task() {
+    let task = parse("Stretch daily #health !3", now()).into_task();
+    assert_eq!(task.name, "Stretch");
+    assert_eq!(task.tags, vec![String::from("health")]);
+    assert_eq!(task.recurrence, every(1, RecurrenceUnit::Day));
+    let task = parse("Stretch", now()).into_task();
+    assert_eq!(task.priority, 1);
+}
+
+#[test]
+fn month_end_recurrence_clamps() {
+    let from = local(2025, 1, 31, 9, 0);
+    let next = Recurrence {
+        every: 1,
+        unit: RecurrenceUnit::Month,
+    }
+    .next(from);
+    assert_eq!(next, local(2025, 2, 28, 9, 0));
+}
+
+#[test]
+fn changes_leave_out_what_was_not_given() {
+    let changes = parse("!3 #home", now()).into_changes();
+    assert_eq!(changes.priority, Some(3));
+    assert_eq!(changes.tags, Some(vec![String::from("home")]));
+    assert_eq!(changes.deadline, None);
+    assert_eq!(changes.paused, None);
+
+    let changes = parse("friday 5pm", now()).into_changes();
+    assert_eq!(changes.deadline, Some(Some(local(2025, 3, 14, 17, 0))));
+    assert_eq!(changes.tags, None);
+}
+
+#[test]
+fn estimates() {
+    let table = [
+        ("Write report ~2h", Some(120)),
+        ("Write report ~45m", Some(45)),
+        ("Write report ~1h30m", Some(90)),
+        ("Write report ~20", Some(20)),
+        ("Write report ~", None),
+        ("Write report ~soon", None),
+    ];
+    for (text, estimate) in table {
+        assert_eq!(parse(text, now()).estimate, estimate, "{}", text);
+    }
+    assert_eq!(parse("Write report ~2h", now()).name, "Write report");
+}
@@ -0,0 +1,127 @@
+use std::rc::Rc;
+
+use chrono::Local;
+use nasin::parser;
+use nasin::project::{FairShare, Filter, Project};
+use nasin::scheduler::{Task, Tasks};
+use nasin::storage::MemoryStorage;
+
+mod common;
+use common::step;
+
+fn task_in(tasks: &mut Tasks, name: &str, priority: u8, project: &str) {
+    let mut task = Task::new(String::from(name), priority, None);
+    task.project = Some(tasks.project_named(project));
+    tasks.add(task);
+}
+
+// A big project with lots to do, and a small one at a lower priority
+fn two_projects() -> Tasks {
+    let mut tasks = Tasks::new();
+    task_in(&mut tasks, "A1", 1, "Big");
+    task_in(&mut tasks, "A2", 1, "Big");
+    task_in(&mut tasks, "A3", 1, "Big");
+    task_in(&mut tasks, "B1", 3, "Small");
+    tasks
+}
+
+#[test]
+fn projects_are_found_by_name() {
+    let mut tasks = Tasks::new();
+    let id = tasks.project_named("Website");
+    assert_eq!(tasks.project_named("website"), id);
+    let other = tasks.project_named("Garden");
+    assert_ne!(other, id);
+    assert_ne!(
+        tasks.project(id).unwrap().color,
+        tasks.project(other).unwrap().color
+    );
+}
+
+#[test]
+fn one_project_wins_without_balancing() {
+    let mut tasks = two_projects();
+    let worked: Vec<String> = (0..3).map(|_| step(&mut tasks)).collect();
+    assert!(worked.iter().all(|x| x.starts_with('A')));
+}
+
+#[test]
+fn balancing_takes_turns_between_projects() {
+    let mut tasks = two_projects();
+    tasks.set_fair_share(FairShare::Projects);
+    let worked: Vec<String> = (0..4).map(|_| step(&mut tasks)).collect();
+    assert_eq!(worked, vec!["A1", "B1", "A2", "B1"]);
+}
+
+#[test]
+fn project_priority_breaks_ties() {
+    let mut tasks = two_projects();
+    let small = tasks.project_named("Small");
+    let mut project = tasks.project(small).unwrap().clone();
+    project.priority = 0;
+    tasks.update_project(project);
+    tasks.set_fair_share(FairShare::Projects);
+    assert_eq!(step(&mut tasks), "B1");
+    assert_eq!(step(&mut tasks), "A1");
+}
+
+#[test]
+fn archiving_pauses_the_projects_tasks() {
+    let mut tasks = two_projects();
+    let big = tasks.project_named("Big");
+    let mut project = tasks.project(big).unwrap().clone();
+    project.archived = true;
+    tasks.update_project(project.clone());
+    assert_eq!(tasks.tasks[0].name, "B1");
+    assert_eq!(tasks.tasks.iter().filter(|x| x.paused).count(), 3);
+    project.archived = false;
+    tasks.update_project(project);
+    assert!(tasks.tasks.iter().all(|x| !x.paused));
+}
+
+#[test]
+fn removing_a_project_keeps_its_tasks() {
+    let mut tasks = two_projects();
+    let big = tasks.project_named("Big");
+    tasks.remove_project(big);
+    assert!(tasks.project(big).is_none());
+    assert_eq!(tasks.tasks.len(), 4);
+    assert_eq!(
+        tasks.tasks.iter().filter(|x| x.project.is_none()).count(),
+        3
+    );
+}
+
+#[test]
+fn quick_add_names_the_project() {
+    let mut tasks = Tasks::new();
+    let parsed = parser::parse("Fix header +website !2", Local::now());
+    assert_eq!(parsed.name, "Fix header");
+    assert_eq!(parsed.project.as_deref(), Some("website"));
+    let id = parsed.project_in(&mut tasks).unwrap();
+    assert_eq!(tasks.project(id).unwrap().name, "website");
+}
+
+#[test]
+fn filter_goes_through_projects() {
+    let mut tasks = two_projects();
+    let big = tasks.project_named("Big");
+    let small = tasks.project_named("Small");
+    let filter = Filter::All.next(&tasks);
+    assert_eq!(filter, Filter::Project(big));
+    assert!(filter.matches(&tasks.tasks[0]));
+    assert_eq!(filter.next(&tasks), Filter::Project(small));
+    assert_eq!(Filter::Project(small).next(&tasks), Filter::NoProject);
+    assert_eq!(Filter::NoProject.next(&tasks), Filter::All);
+}
+
+#[test]
+fn projects_are_saved_with_the_list() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    let id = tasks.add_project(Project::new(String::from("Thesis")));
+    tasks.set_fair_share(FairShare::Projects);
+    let loaded = Tasks::load_from(storage);
+    assert_eq!(loaded.project(id).unwrap().name, "Thesis");
+    assert_eq!(loaded.fair_share, FairShare::Projects);
+}
@@ -0,0 +1,128 @@
+use std::rc::Rc;
+
+use chrono::{Duration, Local};
+use nasin::scheduler::{Changes, Energy, Task, Tasks};
+use nasin::storage::MemoryStorage;
+
+mod common;
+use common::{find, names};
+
+fn id_of(tasks: &Tasks, name: &str) -> u64 {
+    find(tasks, name).id
+}
+
+// Two tasks at priority 1 and two at priority 3
+fn list(tasks: &mut Tasks) {
+    for (name, priority) in [("a", 1), ("b", 1), ("c", 3), ("d", 3)] {
+        tasks.add(Task::new(String::from(name), priority, None));
+    }
+}
+
+#[test]
+fn moving_within_a_priority_sticks() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    list(&mut tasks);
+    assert!(tasks.move_task(id_of(&tasks, "b"), 0));
+    assert_eq!(names(&tasks.tasks), vec!["b", "a", "c", "d"]);
+    // Anything else that sorts the list leaves it where it was put
+    tasks.change(&[id_of(&tasks, "d")], &Changes::default());
+    assert_eq!(names(&tasks.tasks), vec!["b", "a", "c", "d"]);
+    assert_eq!(
+        names(&Tasks::load_from(storage).tasks),
+        vec!["b", "a", "c", "d"]
+    );
+}
+
+#[test]
+fn moving_up_takes_the_priority_of_the_task_passed() {
+    let mut tasks = Tasks::new();
+    list(&mut tasks);
+    tasks.move_task(id_of(&tasks, "c"), 0);
+    assert_eq!(names(&tasks.tasks), vec!["c", "a", "b", "d"]);
+    assert_eq!(tasks.tasks[0].priority, 1);
+    assert_eq!(tasks.tasks[0].base_priority(), 1);
+}
+
+#[test]
+fn moving_down_lowers_the_priority_for_good() {
+    let mut tasks = Tasks::new();
+    list(&mut tasks);
+    tasks.move_task(id_of(&tasks, "a"), 2);
+    assert_eq!(names(&tasks.tasks), vec!["b", "c", "a", "d"]);
+    let a = tasks.find(id_of(&tasks, "a")).unwrap();
+    assert_eq!((a.priority, a.base_priority()), (3, 3));
+    // Once worked on, it goes back to its new priority
+    tasks.step();
+    tasks.step();
+    let a = tasks.find(id_of(&tasks, "a")).unwrap();
+    assert_eq!(a.base_priority(), 3);
+}
+
+#[test]
+fn tasks_with_deadlines_keep_their_deadline_priority() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(String::from("a"), 5, None));
+    tasks.add(Task::new(
+        String::from("due"),
+        1,
+        Some(Local::now() + Duration::hours(1)),
+    ));
+    let due = id_of(&tasks, "due");
+    let priority = tasks.find(due).unwrap().priority;
+    assert_eq!(names(&tasks.tasks), vec!["due", "a"]);
+    tasks.move_task(due, 1);
+    assert_eq!(names(&tasks.tasks), vec!["due", "a"]);
+    assert_eq!(tasks.find(due).unwrap().priority, priority);
+}
+
+#[test]
+fn paused_tasks_stay_put() {
+    let mut tasks = Tasks::new();
+    list(&mut tasks);
+    let d = tasks.find(id_of(&tasks, "d")).unwrap().clone();
+    tasks.toggle_pause(&d);
+    assert!(!tasks.move_task(d.id, 0));
+    assert!(!tasks.move_task(id_of(&tasks, "a"), 3));
+    assert_eq!(names(&tasks.tasks), vec!["a", "b", "c", "d"]);
+}
+
+#[test]
+fn moves_can_be_undone() {
+    let mut tasks = Tasks::new();
+    list(&mut tasks);
+    tasks.move_task(id_of(&tasks, "d"), 0);
+    assert!(tasks.undo());
+    assert_eq!(names(&tasks.tasks), vec!["a", "b", "c", "d"]);
+    assert_eq!(tasks.find(id_of(&tasks, "d")).unwrap().priority, 3);
+}
+
+#[test]
+fn overdue_tasks_stay_ahead() {
+    let mut tasks = Tasks::new();
+    tasks.add(Task::new(
+        String::from("late"),
+        1,
+        Some(Local::now() - Duration::hours(1)),
+    ));
+    list(&mut tasks);
+    assert!(tasks.move_task(id_of(&tasks, "d"), 0));
+    assert_eq!(names(&tasks.tasks), vec!["late", "d", "a", "b", "c"]);
+    let d = tasks.find(id_of(&tasks, "d")).unwrap();
+    assert_eq!((d.priority, d.base_priority()), (1, 1));
+}
+
+#[test]
+fn energy_matching_still_applies_after_a_move() {
+    let mut tasks = Tasks::new();
+    let mut deep = Task::new(String::from("deep"), 1, None);
+    deep.energy = Some(Energy::High);
+    tasks.add(deep);
+    list(&mut tasks);
+    tasks.set_energy(Some(Energy::Low));
+    // Two levels off, it goes as if it were priority 3
+    assert_eq!(names(&tasks.tasks), vec!["a", "b", "deep", "c", "d"]);
+    assert!(tasks.move_task(id_of(&tasks, "deep"), 0));
+    assert_eq!(names(&tasks.tasks), vec!["a", "b", "deep", "c", "d"]);
+    assert_eq!(tasks.find(id_of(&tasks, "deep")).unwrap().priority, 1);
+}
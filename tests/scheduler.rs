@@ -0,0 +1,481 @@
+use std::rc::Rc;
+
+use chrono::{Days, Duration, Local};
+use nasin::scheduler::{
+    AgingPolicy, Changes, EventKind, Recurrence, RecurrenceUnit, Snooze, Task, Tasks, HISTORY_LIMIT,
+};
+use nasin::storage::{MemoryStorage, Storage};
+
+mod common;
+use common::{find, list, local, step, task};
+
+#[test]
+fn highest_priority_runs_first() {
+    let mut tasks = list(vec![task("Low", 5), task("High", 1), task("Middle", 3)]);
+    assert_eq!(step(&mut tasks), "High");
+}
+
+#[test]
+fn equal_priorities_take_turns() {
+    let mut tasks = list(vec![task("A", 1), task("B", 1), task("C", 1)]);
+    let order: Vec<String> = (0..6).map(|_| step(&mut tasks)).collect();
+    for name in ["A", "B", "C"] {
+        assert_eq!(
+            order.iter().filter(|x| *x == name).count(),
+            2,
+            "{:?}",
+            order
+        );
+    }
+}
+
+#[test]
+fn aging_lets_low_priorities_run() {
+    let mut tasks = list(vec![task("Important", 1), task("Someday", 4)]);
+    let order: Vec<String> = (0..8).map(|_| step(&mut tasks)).collect();
+    assert!(order.iter().any(|x| x == "Someday"), "{:?}", order);
+}
+
+#[test]
+fn aging_promotes_the_oldest_task() {
+    let mut tasks = list(vec![task("Important", 1), task("Someday", 4)]);
+    let promoted = tasks.step().unwrap();
+    assert_eq!(promoted.name, "Someday");
+    assert_eq!(promoted.priority, 3);
+    assert_eq!(find(&tasks, "Someday").priority, 3);
+}
+
+#[test]
+fn priority_is_reset_after_running() {
+    let mut tasks = list(vec![task("Important", 1), task("Someday", 4)]);
+    while step(&mut tasks) != "Someday" {}
+    assert_eq!(find(&tasks, "Someday").priority, 4);
+}
+
+#[test]
+fn paused_tasks_go_last_and_are_skipped() {
+    let mut tasks = list(vec![task("Paused", 1), task("Other", 5)]);
+    let paused = find(&tasks, "Paused").clone();
+    tasks.toggle_pause(&paused);
+    assert_eq!(tasks.tasks.last().unwrap().name, "Paused");
+    for _ in 0..5 {
+        assert_eq!(step(&mut tasks), "Other");
+    }
+    let paused = find(&tasks, "Paused").clone();
+    tasks.toggle_pause(&paused);
+    assert_eq!(tasks.tasks[0].name, "Paused");
+}
+
+#[test]
+fn finishing_removes_the_current_task() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    tasks.step_and_finish();
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.tasks[0].name, "B");
+    tasks.step_and_finish();
+    assert!(tasks.tasks.is_empty());
+    assert_eq!(tasks.step_and_finish(), None);
+}
+
+#[test]
+fn finishing_a_recurring_task_adds_the_next_one() {
+    let deadline = Local::now() + Duration::days(1);
+    let mut repeating = Task::new(String::from("Stretch"), 1, Some(deadline));
+    repeating.recurrence = Some(Recurrence {
+        every: 1,
+        unit: RecurrenceUnit::Week,
+    });
+    let mut tasks = list(vec![repeating]);
+    tasks.step_and_finish();
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.tasks[0].deadline, Some(deadline + Duration::weeks(1)));
+}
+
+#[test]
+fn the_next_occurrence_of_an_overdue_task_is_in_the_future() {
+    let deadline = Local::now() - Duration::days(10);
+    let mut chore = Task::new(String::from("Water plants"), 1, Some(deadline));
+    chore.recurrence = Some(Recurrence {
+        every: 3,
+        unit: RecurrenceUnit::Day,
+    });
+    let mut tasks = list(vec![chore]);
+    tasks.step_and_finish();
+    // Four periods on, rather than one that's already overdue
+    let next = tasks.tasks[0].deadline.unwrap();
+    assert_eq!(next.date_naive(), deadline.date_naive() + Days::new(12));
+    assert_eq!(next.time(), deadline.time());
+}
+
+#[test]
+fn removing_a_task() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let a = find(&tasks, "A").clone();
+    tasks.remove(a);
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.history.last().unwrap().kind, EventKind::Removed);
+}
+
+#[test]
+fn tasks_get_unique_ids() {
+    let tasks = list(vec![task("A", 1), task("B", 1), task("C", 1)]);
+    let mut ids: Vec<u64> = tasks.tasks.iter().map(|x| x.id).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+    assert!(!ids.contains(&0));
+}
+
+#[test]
+fn changes_are_saved_to_storage() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    assert!(tasks.tasks.is_empty());
+    assert_eq!(storage.saved(), None);
+
+    tasks.add(task("A", 2));
+    tasks.add(task("B", 1));
+    tasks.step();
+    assert!(storage.saved().is_some());
+
+    let loaded = Tasks::load_from(storage.clone());
+    assert!(loaded.tasks == tasks.tasks);
+    assert_eq!(loaded.history.len(), tasks.history.len());
+}
+
+#[test]
+fn reloading_picks_up_changes_saved_elsewhere() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    tasks.add(task("A", 1));
+    tasks.add(task("B", 2));
+    tasks.remove(find(&tasks, "B").clone());
+    // Nothing else has saved, so there's nothing to pick up
+    assert!(!tasks.reload());
+    assert!(tasks.can_undo());
+
+    let mut elsewhere = Tasks::load_from(storage.clone());
+    elsewhere.add(task("C", 1));
+    assert!(tasks.reload());
+    assert_eq!(tasks.tasks.len(), 2);
+    // Undoing now would lose C
+    assert!(!tasks.can_undo());
+    tasks.step();
+    let loaded = Tasks::load_from(storage);
+    assert!(loaded.tasks == tasks.tasks);
+}
+
+#[test]
+fn history_is_kept_from_growing_forever() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let added = tasks.history[0].clone();
+    tasks.history = vec![added; HISTORY_LIMIT];
+    tasks.step();
+    assert_eq!(tasks.history.len(), HISTORY_LIMIT);
+    assert_eq!(tasks.history.last().unwrap().kind, EventKind::Stepped);
+
+    // Undoing still takes back just what was undone
+    tasks.remove(find(&tasks, "B").clone());
+    assert!(tasks.undo());
+    assert_eq!(tasks.tasks.len(), 2);
+    assert_eq!(tasks.history.len(), HISTORY_LIMIT - 1);
+    assert_eq!(tasks.history.last().unwrap().kind, EventKind::Stepped);
+}
+
+#[test]
+fn simulating_does_not_save() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    tasks.add(task("A", 1));
+    let saved = storage.saved();
+    tasks.simulate(5);
+    assert_eq!(storage.saved(), saved);
+    assert!(storage.load().unwrap().is_some());
+}
+
+fn list_with_policy(tasks: Vec<Task>, policy: AgingPolicy) -> Tasks {
+    let mut list = list(tasks);
+    list.set_aging_policy(policy);
+    list
+}
+
+#[test]
+fn boost_amount_and_maximum() {
+    let policy = AgingPolicy {
+        boost: 3,
+        max_boost: 4,
+        ..AgingPolicy::default()
+    };
+    let mut tasks = list_with_policy(vec![task("Important", 1), task("Someday", 9)], policy);
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 6);
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 5);
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 5);
+}
+
+#[test]
+fn boost_threshold_and_increment() {
+    let policy = AgingPolicy {
+        threshold: 3,
+        ..AgingPolicy::default()
+    };
+    let mut tasks = list_with_policy(vec![task("Important", 1), task("Someday", 9)], policy);
+    tasks.step();
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 9);
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 8);
+
+    let policy = AgingPolicy {
+        threshold: 3,
+        increment: 3,
+        ..AgingPolicy::default()
+    };
+    let mut tasks = list_with_policy(vec![task("Important", 1), task("Someday", 9)], policy);
+    tasks.step();
+    assert_eq!(find(&tasks, "Someday").priority, 8);
+}
+
+#[test]
+fn several_tasks_boosted_per_step() {
+    let policy = AgingPolicy {
+        boosted_per_step: 2,
+        ..AgingPolicy::default()
+    };
+    let mut tasks = list_with_policy(
+        vec![task("Important", 1), task("B", 5), task("C", 7)],
+        policy,
+    );
+    tasks.step();
+    assert_eq!(find(&tasks, "B").priority, 4);
+    assert_eq!(find(&tasks, "C").priority, 6);
+}
+
+#[test]
+fn aging_policy_is_saved_with_the_list() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    let policy = AgingPolicy {
+        boost: 2,
+        ..AgingPolicy::default()
+    };
+    tasks.set_aging_policy(policy);
+    assert_eq!(Tasks::load_from(storage).aging_policy, policy);
+}
+
+#[test]
+fn undoing_a_removal() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    assert!(!tasks.can_undo());
+    let a = find(&tasks, "A").clone();
+    let history = tasks.history.len();
+    tasks.remove(a);
+    assert!(tasks.can_undo());
+    assert!(tasks.undo());
+    assert_eq!(tasks.tasks.len(), 2);
+    assert_eq!(tasks.history.len(), history);
+    assert!(!tasks.undo());
+}
+
+#[test]
+fn undoing_a_finish() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let before = tasks.tasks.clone();
+    tasks.step_and_finish();
+    tasks.undo();
+    assert_eq!(tasks.tasks, before);
+}
+
+#[test]
+fn finishing_with_a_paused_head_keeps_undo() {
+    let mut tasks = list(vec![task("A", 1)]);
+    let a = find(&tasks, "A").id;
+    tasks.change(
+        &[a],
+        &Changes {
+            paused: Some(true),
+            ..Changes::default()
+        },
+    );
+    assert!(tasks.can_undo());
+    assert_eq!(tasks.step_and_finish(), None);
+    assert_eq!(tasks.tasks.len(), 1);
+    // The pause can still be undone
+    assert!(tasks.can_undo());
+    assert!(tasks.undo());
+    assert!(!tasks.tasks[0].paused);
+}
+
+#[test]
+fn finishing_by_id_leaves_the_head_alone() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let b = find(&tasks, "B").id;
+    tasks.finish(b);
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.tasks[0].name, "A");
+    assert!(tasks.can_undo());
+    let a = find(&tasks, "A").clone();
+    tasks.toggle_pause(&a);
+    assert_eq!(tasks.finish(a.id), None);
+    assert!(!tasks.can_undo());
+    assert_eq!(tasks.tasks.len(), 1);
+}
+
+#[test]
+fn removing_by_id_works_after_the_task_changed() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let asked_about = find(&tasks, "B").clone();
+    // B ages while a dialog asks about it
+    tasks.step();
+    assert!(*find(&tasks, "B") != asked_about);
+    tasks.remove_all(&[asked_about.id]);
+    assert_eq!(tasks.tasks.len(), 1);
+    assert_eq!(tasks.tasks[0].name, "A");
+}
+
+#[test]
+fn other_changes_stop_undo() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    tasks.step_and_finish();
+    tasks.step();
+    assert!(!tasks.can_undo());
+    assert!(!tasks.undo());
+    assert_eq!(tasks.tasks.len(), 1);
+}
+
+#[test]
+fn bulk_changes_apply_to_every_task_given() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2), task("C", 3)]);
+    let ids = [find(&tasks, "A").id, find(&tasks, "C").id];
+    tasks.change(
+        &ids,
+        &Changes {
+            paused: Some(true),
+            priority: Some(5),
+            tags: Some(vec![String::from("later")]),
+            ..Changes::default()
+        },
+    );
+    for name in ["A", "C"] {
+        let task = find(&tasks, name);
+        assert!(task.paused);
+        assert_eq!(task.priority, 5);
+        assert_eq!(task.tags, vec![String::from("later")]);
+    }
+    assert!(!find(&tasks, "B").paused);
+    assert_eq!(tasks.tasks[0].name, "B");
+    let paused = tasks.history.iter().filter(|x| x.kind == EventKind::Paused);
+    assert_eq!(paused.count(), 2);
+}
+
+#[test]
+fn bulk_deadlines_set_priority() {
+    let mut tasks = list(vec![task("A", 5), task("B", 5)]);
+    let ids: Vec<u64> = tasks.tasks.iter().map(|x| x.id).collect();
+    let deadline = Local::now() + Duration::hours(1);
+    tasks.change(
+        &ids,
+        &Changes {
+            deadline: Some(Some(deadline)),
+            ..Changes::default()
+        },
+    );
+    for task in &tasks.tasks {
+        assert_eq!(task.deadline, Some(deadline));
+        assert!(task.priority < 5);
+    }
+}
+
+#[test]
+fn bulk_changes_are_undone_together() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2), task("C", 3)]);
+    let before = tasks.tasks.clone();
+    let ids = [find(&tasks, "A").id, find(&tasks, "B").id];
+    tasks.remove_all(&ids);
+    assert_eq!(tasks.tasks.len(), 1);
+    assert!(tasks.undo());
+    assert_eq!(tasks.tasks, before);
+
+    tasks.change(
+        &ids,
+        &Changes {
+            paused: Some(true),
+            ..Changes::default()
+        },
+    );
+    assert!(tasks.undo());
+    assert_eq!(tasks.tasks, before);
+}
+
+#[test]
+fn working_on_a_task_runs_it_instead_of_the_head() {
+    let mut tasks = list(vec![task("A", 2), task("B", 3)]);
+    let b = find(&tasks, "B").id;
+    let promoted = tasks.work_on(b).unwrap();
+    // The head waited while B was worked on, so it got promoted
+    assert_eq!(promoted.name, "A");
+    assert_eq!(find(&tasks, "A").priority, 1);
+    assert_eq!(find(&tasks, "B").priority, 3);
+    assert_eq!(find(&tasks, "B").steps_waited(), 0);
+    let last = tasks.history.last().unwrap();
+    assert_eq!(last.kind, EventKind::Stepped);
+    assert_eq!(last.task_id, b);
+}
+
+#[test]
+fn working_on_a_missing_task_does_nothing() {
+    let mut tasks = list(vec![task("A", 1)]);
+    let before = tasks.tasks.clone();
+    assert!(tasks.work_on(1000).is_none());
+    assert_eq!(tasks.tasks, before);
+}
+
+#[test]
+fn snoozed_tasks_wake_up_when_their_time_comes() {
+    let mut tasks = list(vec![task("A", 1), task("B", 2)]);
+    let a = find(&tasks, "A").id;
+    let now = Local::now();
+    tasks.snooze(&[a], now + Duration::hours(2));
+    assert!(find(&tasks, "A").paused);
+    assert_eq!(step(&mut tasks), "B");
+
+    assert!(tasks.wake(now + Duration::hours(1)).is_empty());
+    let woken = tasks.wake(now + Duration::hours(2));
+    assert_eq!(woken.len(), 1);
+    assert!(!find(&tasks, "A").paused);
+    assert_eq!(find(&tasks, "A").snoozed_until, None);
+    assert_eq!(tasks.history.last().unwrap().kind, EventKind::Resumed);
+}
+
+#[test]
+fn loading_wakes_snoozed_tasks() {
+    let storage = Rc::new(MemoryStorage::new());
+    let mut tasks = Tasks::load_from(storage.clone());
+    tasks.add(task("A", 1));
+    let a = find(&tasks, "A").id;
+    tasks.snooze(&[a], Local::now() - Duration::minutes(1));
+    let tasks = Tasks::load_from(storage);
+    assert!(!find(&tasks, "A").paused);
+}
+
+#[test]
+fn resuming_by_hand_forgets_the_snooze() {
+    let mut tasks = list(vec![task("A", 1)]);
+    let a = find(&tasks, "A").clone();
+    tasks.snooze(&[a.id], Local::now() + Duration::days(1));
+    let snoozed = find(&tasks, "A").clone();
+    tasks.toggle_pause(&snoozed);
+    assert!(!find(&tasks, "A").paused);
+    assert_eq!(find(&tasks, "A").snoozed_until, None);
+}
+
+#[test]
+fn snooze_presets() {
+    let now = local(2025, 3, 12, 10, 0);
+    assert_eq!(Snooze::LaterToday.until(now), local(2025, 3, 12, 13, 0));
+    assert_eq!(Snooze::Tomorrow.until(now), local(2025, 3, 13, 9, 0));
+    // Wednesday, so next week starts on the 17th
+    assert_eq!(Snooze::NextWeek.until(now), local(2025, 3, 17, 9, 0));
+}
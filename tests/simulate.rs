@@ -0,0 +1,106 @@
+use chrono::{DateTime, Duration, Local};
+use nasin::scheduler::{Task, Tasks};
+
+mod common;
+use common::{now, task};
+
+fn due(name: &str, priority: u8, deadline: DateTime<Local>) -> Task {
+    Task::new(String::from(name), priority, Some(deadline))
+}
+
+// Build a list without touching the saved one
+fn tasks(list: Vec<Task>) -> Tasks {
+    let mut tasks = Tasks::new();
+    for (i, mut task) in list.into_iter().enumerate() {
+        task.id = i as u64 + 1;
+        tasks.tasks.push(task);
+    }
+    tasks.tasks.sort();
+    tasks
+}
+
+#[test]
+fn simulation_does_not_change_the_list() {
+    let tasks = tasks(vec![task("A", 1), task("B", 3)]);
+    let before = tasks.tasks.clone();
+    let forecast = tasks.simulate_from(10, now(), Duration::minutes(25));
+    assert_eq!(forecast.steps.len(), 10);
+    assert!(tasks.tasks == before);
+    assert!(tasks.history.is_empty());
+}
+
+#[test]
+fn steps_are_spaced_by_the_step_length() {
+    let tasks = tasks(vec![task("A", 1)]);
+    let forecast = tasks.simulate_from(3, now(), Duration::minutes(25));
+    let times: Vec<_> = forecast.steps.iter().map(|x| x.time).collect();
+    assert_eq!(
+        times,
+        vec![
+            now(),
+            now() + Duration::minutes(25),
+            now() + Duration::minutes(50)
+        ]
+    );
+}
+
+#[test]
+fn lower_priorities_get_their_turn() {
+    let tasks = tasks(vec![task("A", 1), task("B", 3)]);
+    let forecast = tasks.simulate_from(6, now(), Duration::minutes(25));
+    let order: Vec<&str> = forecast.steps.iter().map(|x| x.name.as_str()).collect();
+    assert!(order.contains(&"B"), "{:?}", order);
+}
+
+#[test]
+fn paused_tasks_are_never_worked_on() {
+    let mut paused = task("Paused", 1);
+    paused.paused = true;
+    let tasks = tasks(vec![paused, task("B", 5)]);
+    let forecast = tasks.simulate_from(8, now(), Duration::minutes(25));
+    assert!(forecast.steps.iter().all(|x| x.name == "B"));
+
+    let mut paused = task("Paused", 1);
+    paused.paused = true;
+    let forecast = self::tasks(vec![paused]).simulate_from(8, now(), Duration::minutes(25));
+    assert!(forecast.steps.is_empty());
+}
+
+#[test]
+fn reports_deadlines_that_will_be_missed() {
+    let soon = now() + Duration::minutes(30);
+    let tasks = tasks(vec![
+        due("A", 5, soon),
+        due("B", 5, soon),
+        due("C", 5, soon),
+    ]);
+    let forecast = tasks.simulate_from(3, now(), Duration::minutes(25));
+    assert_eq!(forecast.missed_deadlines.len(), 1);
+    assert_eq!(
+        forecast.missed_deadlines[0].first_worked_on,
+        Some(now() + Duration::minutes(50))
+    );
+
+    let forecast = tasks.simulate_from(1, now(), Duration::minutes(40));
+    assert_eq!(forecast.missed_deadlines.len(), 2);
+    assert!(forecast
+        .missed_deadlines
+        .iter()
+        .all(|x| x.first_worked_on.is_none()));
+
+    let far = now() + Duration::days(30);
+    let tasks = self::tasks(vec![due("Later", 5, far)]);
+    let forecast = tasks.simulate_from(4, now(), Duration::minutes(25));
+    assert!(forecast.missed_deadlines.is_empty());
+}
+
+#[test]
+fn snoozed_tasks_are_worked_on_once_they_wake_up() {
+    let mut tasks = tasks(vec![task("Snoozed", 1)]);
+    tasks.tasks[0].paused = true;
+    tasks.tasks[0].snoozed_until = Some(now() + Duration::minutes(60));
+    let forecast = tasks.simulate_from(4, now(), Duration::minutes(25));
+    let times: Vec<_> = forecast.steps.iter().map(|x| x.time).collect();
+    assert_eq!(times, vec![now() + Duration::minutes(75)]);
+    assert!(tasks.tasks[0].paused);
+}
@@ -0,0 +1,129 @@
+use chrono::{Duration, NaiveDate};
+use nasin::scheduler::{Event, EventKind};
+use nasin::stats::{Starvation, Stats, RECENT_RUNS};
+
+mod common;
+use common::now;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 3, day).unwrap()
+}
+
+fn event(minutes_ago: i64, kind: EventKind, task_id: u64, steps_waited: u32) -> Event {
+    let (name, base_priority, tag) = match task_id {
+        1 => ("Write", 1, "work"),
+        _ => ("Dishes", 2, "home"),
+    };
+    Event {
+        time: now() - Duration::minutes(minutes_ago),
+        kind,
+        task_id,
+        name: String::from(name),
+        base_priority,
+        tags: vec![String::from(tag)],
+        project: None,
+        steps_waited,
+    }
+}
+
+// Two tasks taking turns ten minutes at a time until both are done
+fn history() -> Vec<Event> {
+    vec![
+        event(60, EventKind::Added, 1, 0),
+        event(60, EventKind::Added, 2, 0),
+        event(50, EventKind::Stepped, 1, 0),
+        event(40, EventKind::Stepped, 2, 1),
+        event(30, EventKind::Finished, 1, 1),
+        event(25, EventKind::Promoted, 2, 0),
+        event(20, EventKind::Finished, 2, 2),
+    ]
+}
+
+#[test]
+fn completions_per_day() {
+    let mut history = history();
+    history.push(event(24 * 60, EventKind::Finished, 1, 0));
+    let stats = Stats::compute(&history, now(), 3);
+    assert_eq!(
+        stats.completions_per_day,
+        vec![(date(10), 0), (date(11), 1), (date(12), 2)]
+    );
+    assert_eq!(stats.total_completions(), 3);
+}
+
+#[test]
+fn average_wait() {
+    let stats = Stats::compute(&history(), now(), 1);
+    // Waits of 10, 20, 20 and 20 minutes
+    assert_eq!(stats.average_wait, Some(Duration::seconds(17 * 60 + 30)));
+    assert_eq!(Stats::compute(&[], now(), 1).average_wait, None);
+}
+
+#[test]
+fn time_is_counted_since_the_previous_run() {
+    let stats = Stats::compute(&history(), now(), 1);
+    assert_eq!(
+        stats.time_per_tag,
+        vec![
+            (String::from("home"), Duration::minutes(20)),
+            (String::from("work"), Duration::minutes(10)),
+        ]
+    );
+    assert_eq!(
+        stats.time_per_priority,
+        vec![(1, Duration::minutes(10)), (2, Duration::minutes(20))]
+    );
+}
+
+#[test]
+fn long_gaps_are_breaks() {
+    let history = vec![
+        event(600, EventKind::Stepped, 1, 0),
+        event(60, EventKind::Stepped, 2, 0),
+        event(50, EventKind::Stepped, 1, 0),
+    ];
+    let stats = Stats::compute(&history, now(), 1);
+    assert_eq!(
+        stats.time_per_tag,
+        vec![(String::from("work"), Duration::minutes(10))]
+    );
+}
+
+#[test]
+fn starvation_per_priority() {
+    let stats = Stats::compute(&history(), now(), 1);
+    assert_eq!(
+        stats.starvation,
+        vec![
+            Starvation {
+                priority: 1,
+                runs: 2,
+                promotions: 0,
+                average_steps_waited: 0.5,
+                max_steps_waited: 1,
+            },
+            Starvation {
+                priority: 2,
+                runs: 2,
+                promotions: 1,
+                average_steps_waited: 1.5,
+                max_steps_waited: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn only_the_latest_waits_are_kept() {
+    assert_eq!(
+        Stats::compute(&history(), now(), 1).recent_waits,
+        vec![0, 1, 1, 2]
+    );
+    let history: Vec<Event> = (0..RECENT_RUNS as u32 + 50)
+        .map(|i| event(1000 - i as i64, EventKind::Stepped, 1, i))
+        .collect();
+    let waits = Stats::compute(&history, now(), 1).recent_waits;
+    assert_eq!(waits.len(), RECENT_RUNS);
+    assert_eq!(waits[0], 50);
+    assert_eq!(*waits.last().unwrap(), RECENT_RUNS as u64 + 49);
+}
@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf, rc::Rc};
+
+use nasin::scheduler::{Recurrence, RecurrenceUnit, Task, Tasks};
+use nasin::storage::{FileStorage, MemoryStorage, Storage};
+
+// A file of its own for each test, since they run at the same time
+fn path(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("nasin-{}-{}.json", name, std::process::id()));
+    path
+}
+
+#[test]
+fn a_missing_or_empty_file_is_an_empty_list() {
+    let path = path("missing");
+    let _ = fs::remove_file(&path);
+    assert!(FileStorage::new(path.clone()).load().unwrap().is_none());
+    fs::write(&path, "").unwrap();
+    assert!(FileStorage::new(path.clone()).load().unwrap().is_none());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn a_list_that_cant_be_read_is_left_alone() {
+    let path = path("broken");
+    fs::write(&path, "{ not json").unwrap();
+    assert!(FileStorage::new(path.clone()).load().is_err());
+    let mut tasks = Tasks::load_from(Rc::new(FileStorage::new(path.clone())));
+    assert!(tasks.tasks.is_empty());
+    tasks.add(Task::new(String::from("A"), 1, None));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn recurring_every_zero_units_is_fixed_on_load() {
+    // Quick add used to take "every 0 days"
+    let storage = Rc::new(MemoryStorage::new());
+    let mut chore = Task::new(String::from("Spin"), 1, None);
+    chore.recurrence = Some(Recurrence {
+        every: 0,
+        unit: RecurrenceUnit::Day,
+    });
+    Tasks::load_from(storage.clone()).add(chore);
+    let path = path("every-zero");
+    fs::write(&path, storage.saved().unwrap()).unwrap();
+    let tasks = Tasks::load_from(Rc::new(FileStorage::new(path.clone())));
+    assert_eq!(tasks.tasks[0].recurrence.unwrap().every, 1);
+    fs::remove_file(path).unwrap();
+}